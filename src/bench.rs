@@ -0,0 +1,288 @@
+use std::fmt;
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufReader, BufWriter};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DynSolution, Error, Part};
+
+#[derive(Debug, Copy, Clone)]
+pub struct Config {
+    /// Number of untimed runs before measuring.
+    pub warmup: usize,
+    /// Number of timed runs.
+    pub iterations: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            warmup: 3,
+            iterations: 20,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Parse,
+    PartOne,
+    PartTwo,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::PartOne => write!(f, "part one"),
+            Stage::PartTwo => write!(f, "part two"),
+        }
+    }
+}
+
+/// Timings for a single stage of a day, all durations are in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Measurement {
+    pub day: u8,
+    pub stage: Stage,
+    pub iterations: usize,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub p95_ns: u64,
+}
+
+impl Measurement {
+    fn new(day: u8, stage: Stage, mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "Need at least one sample");
+        samples.sort();
+
+        let nanos = |d: Duration| d.as_nanos() as u64;
+        let percentile = |p: usize| {
+            let idx = (samples.len() * p).div_ceil(100).saturating_sub(1);
+
+            nanos(samples[idx])
+        };
+
+        Self {
+            day,
+            stage,
+            iterations: samples.len(),
+            min_ns: nanos(samples[0]),
+            median_ns: percentile(50),
+            p95_ns: percentile(95),
+        }
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {:02} {:<8} min: {:>12?} median: {:>12?} p95: {:>12?}",
+            self.day,
+            self.stage.to_string(),
+            Duration::from_nanos(self.min_ns),
+            Duration::from_nanos(self.median_ns),
+            Duration::from_nanos(self.p95_ns),
+        )
+    }
+}
+
+/// Benchmark parsing and both parts of a day separately.
+pub fn bench(
+    day: u8,
+    solution: &dyn DynSolution,
+    input: &str,
+    config: Config,
+) -> Result<Vec<Measurement>, Error> {
+    let parse = measure(config, || solution.parse(input))?;
+    let parsed = solution.parse(input)?;
+
+    let part_one = measure(config, || Ok(parsed.solve(Part::One)))?;
+    let part_two = measure(config, || Ok(parsed.solve(Part::Two)))?;
+
+    Ok(vec![
+        Measurement::new(day, Stage::Parse, parse),
+        Measurement::new(day, Stage::PartOne, part_one),
+        Measurement::new(day, Stage::PartTwo, part_two),
+    ])
+}
+
+fn measure<F, T>(config: Config, mut f: F) -> Result<Vec<Duration>, Error>
+where
+    F: FnMut() -> Result<T, Error>,
+{
+    for _ in 0..config.warmup {
+        black_box(f()?);
+    }
+
+    (0..config.iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            let result = black_box(f());
+            let elapsed = start.elapsed();
+
+            result.map(|_| elapsed)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Report {
+    pub measurements: Vec<Measurement>,
+}
+
+impl Report {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open `{path}`: {e}"))?;
+
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| format!("Failed to read benchmark report `{path}`: {e}"))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create `{path}`: {e}"))?;
+
+        serde_json::to_writer_pretty(BufWriter::new(file), self)
+            .map_err(|e| format!("Failed to write benchmark report `{path}`: {e}"))
+    }
+
+    /// Compare against a baseline, flagging stages whose median got slower by more than
+    /// `threshold`, i.e. `0.1` for 10%.
+    ///
+    /// Stages that are missing from the baseline are ignored, as are stages with a baseline
+    /// median of zero, which no relative change can be measured against.
+    pub fn regressions(&self, baseline: &Report, threshold: f64) -> Vec<Regression> {
+        self.measurements
+            .iter()
+            .filter_map(|current| {
+                let previous = baseline
+                    .measurements
+                    .iter()
+                    .find(|m| m.day == current.day && m.stage == current.stage)
+                    .filter(|previous| previous.median_ns > 0)?;
+                let change = (current.median_ns as f64 - previous.median_ns as f64)
+                    / previous.median_ns as f64;
+
+                (change > threshold).then_some(Regression {
+                    day: current.day,
+                    stage: current.stage,
+                    baseline_ns: previous.median_ns,
+                    current_ns: current.median_ns,
+                    change,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub day: u8,
+    pub stage: Stage,
+    pub baseline_ns: u64,
+    pub current_ns: u64,
+    /// Relative change of the median, `0.5` means 50% slower.
+    pub change: f64,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {:02} {} regressed by {:.1}%: {:?} -> {:?}",
+            self.day,
+            self.stage,
+            self.change * 100.0,
+            Duration::from_nanos(self.baseline_ns),
+            Duration::from_nanos(self.current_ns),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{bench, Config, Measurement, Report, Stage};
+    use crate::day01::Day01;
+
+    #[test]
+    fn test_measurement_stats() {
+        let samples = (1..=100).rev().map(Duration::from_nanos).collect();
+        let measurement = Measurement::new(1, Stage::Parse, samples);
+
+        assert_eq!(measurement.iterations, 100);
+        assert_eq!(measurement.min_ns, 1);
+        assert_eq!(measurement.median_ns, 50);
+        assert_eq!(measurement.p95_ns, 95);
+    }
+
+    #[test]
+    fn test_bench() {
+        let config = Config {
+            warmup: 1,
+            iterations: 5,
+        };
+        let measurements = bench(1, &Day01, "1000\n2000\n\n3000", config).unwrap();
+
+        let stages: Vec<_> = measurements.iter().map(|m| m.stage).collect();
+        assert_eq!(stages, vec![Stage::Parse, Stage::PartOne, Stage::PartTwo]);
+        assert!(measurements.iter().all(|m| m.iterations == 5));
+    }
+
+    #[test]
+    fn test_regressions() {
+        let measurement = |stage, median_ns| Measurement {
+            day: 1,
+            stage,
+            iterations: 1,
+            min_ns: median_ns,
+            median_ns,
+            p95_ns: median_ns,
+        };
+        let baseline = Report {
+            measurements: vec![
+                measurement(Stage::Parse, 100),
+                measurement(Stage::PartOne, 100),
+            ],
+        };
+        let current = Report {
+            measurements: vec![
+                measurement(Stage::Parse, 105),
+                measurement(Stage::PartOne, 150),
+                measurement(Stage::PartTwo, 1000),
+            ],
+        };
+
+        let json = serde_json::to_string(&current).unwrap();
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), current);
+
+        let regressions = current.regressions(&baseline, 0.1);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].stage, Stage::PartOne);
+        assert!((regressions[0].change - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_regressions_zero_baseline() {
+        let measurement = |median_ns| Measurement {
+            day: 1,
+            stage: Stage::PartOne,
+            iterations: 1,
+            min_ns: median_ns,
+            median_ns,
+            p95_ns: median_ns,
+        };
+        let report = |median_ns| Report {
+            measurements: vec![measurement(median_ns)],
+        };
+
+        assert!(report(0).regressions(&report(0), 0.1).is_empty());
+        assert!(report(50).regressions(&report(0), 0.1).is_empty());
+        assert_eq!(report(50).regressions(&report(1), 0.1).len(), 1);
+    }
+}
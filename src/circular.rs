@@ -0,0 +1,199 @@
+/// A circular list where values keep a stable handle, their index in the original order, while
+/// being moved around.
+///
+/// Handles are stored in chunks of roughly `√n`, so looking up or changing the position of a
+/// value takes `O(√n)` rather than the `O(n)` of shifting a flat list.
+#[derive(Debug, Clone)]
+pub struct CircularList<T> {
+    values: Vec<T>,
+    /// Handles in list order.
+    chunks: Vec<Vec<usize>>,
+    /// The chunk each handle is in.
+    chunk_of: Vec<usize>,
+    chunk_size: usize,
+}
+
+impl<T> CircularList<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        let chunk_size = (values.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let mut list = Self {
+            chunks: vec![(0..values.len()).collect()],
+            chunk_of: vec![0; values.len()],
+            values,
+            chunk_size,
+        };
+        list.rebalance();
+
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The value with the given handle.
+    pub fn get(&self, handle: usize) -> &T {
+        &self.values[handle]
+    }
+
+    /// The current position of the value with the given handle.
+    pub fn position(&self, handle: usize) -> usize {
+        let chunk = self.chunk_of[handle];
+        let before: usize = self.chunks[..chunk].iter().map(Vec::len).sum();
+        let within = self.chunks[chunk]
+            .iter()
+            .position(|&h| h == handle)
+            .expect("Handles are always in the chunk they're recorded in");
+
+        before + within
+    }
+
+    /// The handle of the value at `position`, wrapping around the end.
+    pub fn handle_at(&self, position: usize) -> usize {
+        let mut position = position % self.len();
+
+        for chunk in &self.chunks {
+            if position < chunk.len() {
+                return chunk[position];
+            }
+            position -= chunk.len();
+        }
+
+        unreachable!("Position is always within the list")
+    }
+
+    /// Move a value `offset` places forwards, or backwards if negative, wrapping around.
+    ///
+    /// While moving the value isn't part of the list, so moving it `len - 1` places puts it back
+    /// where it was.
+    pub fn move_by(&mut self, handle: usize, offset: i64) {
+        if self.len() <= 1 {
+            return;
+        }
+
+        let from = self.position(handle);
+        self.remove(handle);
+
+        // Reduce the offset first, so adding it to `from` can't overflow
+        let lap = self.len() as i64 - 1;
+        let to = (from as i64 + offset.rem_euclid(lap)) % lap;
+        self.insert(to as usize, handle);
+    }
+
+    /// Values in list order, starting at position 0.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.chunks.iter().flatten().map(|&h| &self.values[h])
+    }
+
+    fn remove(&mut self, handle: usize) {
+        let chunk = &mut self.chunks[self.chunk_of[handle]];
+        let idx = chunk
+            .iter()
+            .position(|&h| h == handle)
+            .expect("Handles are always in the chunk they're recorded in");
+
+        chunk.remove(idx);
+    }
+
+    /// Insert `handle` at `position`, which is at most the number of values in the chunks.
+    fn insert(&mut self, mut position: usize, handle: usize) {
+        let last = self.chunks.len() - 1;
+
+        for (idx, chunk) in self.chunks.iter_mut().enumerate() {
+            if position <= chunk.len() && (position < chunk.len() || idx == last) {
+                chunk.insert(position, handle);
+                self.chunk_of[handle] = idx;
+
+                if chunk.len() > 2 * self.chunk_size {
+                    self.rebalance();
+                }
+
+                return;
+            }
+            position -= chunk.len();
+        }
+
+        unreachable!("Position is always within the list")
+    }
+
+    /// Split the handles into chunks of equal size again.
+    fn rebalance(&mut self) {
+        let handles: Vec<_> = self.chunks.drain(..).flatten().collect();
+
+        self.chunks = handles
+            .chunks(self.chunk_size)
+            .map(<[usize]>::to_vec)
+            .collect();
+        if self.chunks.is_empty() {
+            self.chunks.push(vec![]);
+        }
+
+        for (idx, chunk) in self.chunks.iter().enumerate() {
+            for &handle in chunk {
+                self.chunk_of[handle] = idx;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CircularList;
+
+    #[test]
+    fn test_move_by() {
+        let mut list = CircularList::new(vec!['a', 'b', 'c', 'd', 'e']);
+
+        list.move_by(0, 2);
+        assert_eq!(list.iter().collect::<String>(), "bcade");
+        assert_eq!(list.position(0), 2);
+
+        list.move_by(4, -1);
+        assert_eq!(list.iter().collect::<String>(), "bcaed");
+
+        // Moving by a multiple of `len - 1` is a full lap
+        list.move_by(1, 8);
+        assert_eq!(list.iter().collect::<String>(), "bcaed");
+
+        assert_eq!(list.handle_at(7), 0);
+    }
+
+    #[test]
+    fn test_move_by_extreme_offsets() {
+        let mut list = CircularList::new(vec!['a', 'b', 'c', 'd', 'e']);
+
+        // `i64::MAX` is 3 more than a multiple of the lap of 4
+        list.move_by(0, i64::MAX);
+        assert_eq!(list.iter().collect::<String>(), "bcdae");
+
+        // `i64::MIN` is a whole number of laps
+        list.move_by(0, i64::MIN);
+        assert_eq!(list.iter().collect::<String>(), "bcdae");
+    }
+
+    #[test]
+    fn test_matches_flat_list() {
+        let len = 100;
+        let mut list = CircularList::new((0..len).collect());
+        let mut flat: Vec<usize> = (0..len).collect();
+
+        for step in 0..1000 {
+            let handle = (step * 37) % len;
+            let offset = (step as i64 * 7919) % 1000 - 500;
+
+            list.move_by(handle, offset);
+
+            let from = flat.iter().position(|&h| h == handle).unwrap();
+            flat.remove(from);
+            let to = (from as i64 + offset).rem_euclid(len as i64 - 1);
+            flat.insert(to as usize, handle);
+        }
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), flat);
+        assert!(list.chunks.iter().all(|c| c.len() <= 2 * list.chunk_size));
+    }
+}
@@ -1,27 +1,60 @@
+use crate::{try_parse_lines, Error, Location, Solution};
+
 pub fn star_one(input: &str) -> u64 {
-    parse(input).max().expect("There should be at least on elf")
+    let calories = Day01::parse(input).expect("Failed to parse input");
+
+    Day01::part_one(&calories)
 }
 
 pub fn star_two(input: &str) -> u64 {
-    let mut calories: Vec<u64> = parse(input).collect();
+    let calories = Day01::parse(input).expect("Failed to parse input");
+
+    Day01::part_two(&calories)
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    /// Total calories carried by each elf.
+    type Input = Vec<u64>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input).collect()
+    }
 
-    calories.sort_by(|a, b| b.cmp(a));
+    fn part_one(calories: &Self::Input) -> Self::PartOne {
+        calories
+            .iter()
+            .copied()
+            .max()
+            .expect("There should be at least on elf")
+    }
+
+    fn part_two(calories: &Self::Input) -> Self::PartTwo {
+        let mut calories = calories.clone();
 
-    calories.into_iter().take(3).sum()
+        calories.sort_by(|a, b| b.cmp(a));
+
+        calories.into_iter().take(3).sum()
+    }
 }
 
-fn parse(input: &str) -> impl Iterator<Item = u64> + '_ {
-    input
-        .trim()
-        .split("\n\n")
-        .map(|group| group.lines().flat_map(|l| l.parse::<u64>().ok()).sum())
+fn parse(input: &str) -> impl Iterator<Item = Result<u64, Error>> + '_ {
+    input.trim().split("\n\n").map(move |group| {
+        try_parse_lines::<u64>(group)
+            .sum::<Result<u64, _>>()
+            .map_err(|e| e.offset(Location::of(input, group), group))
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day01};
+    use crate::{Error, Location, Solution};
 
-    const TEST_INPUT: &'static str = r#"""
+    const TEST_INPUT: &str = r#"
 1000
 2000
 3000
@@ -36,7 +69,7 @@ mod tests {
 9000
 
 10000
-"""#;
+"#;
 
     #[test]
     fn test_star_one() {
@@ -47,4 +80,15 @@ mod tests {
     fn test_star_two() {
         assert_eq!(star_two(TEST_INPUT), 45000);
     }
+
+    #[test]
+    fn test_parse_malformed_line() {
+        let result = Day01::parse("1000\n2000\n\n3000\n4k\n");
+
+        let Err(Error::Parse(e)) = result else {
+            panic!("Expected a parse error, got {:?}", result);
+        };
+        assert_eq!(e.location, Location::new(5, 1));
+        assert_eq!(e.text, "4k");
+    }
 }
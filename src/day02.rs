@@ -1,28 +1,52 @@
 use std::str::FromStr;
 
-use crate::parse_lines;
+use crate::{try_parse_lines, Error, Solution};
 
 pub fn star_one(input: &str) -> u64 {
-    score(parse_lines::<Suggestion>(input))
+    let suggestions = Day02::parse(input).expect("Failed to parse input");
+
+    Day02::part_one(&suggestions)
 }
 
 pub fn star_two(input: &str) -> u64 {
-    score(parse_lines::<Suggestion>(input).map(|s| {
-        // reinterpret the input, flipped because we are considering it from the perspective of
-        // the opponent
-        let desired_outcome = match s.you {
-            Action::Rock => Outcome::Win,
-            Action::Paper => Outcome::Draw,
-            Action::Scissors => Outcome::Lose,
-        };
-
-        let action = s.opponent.desired_outcome(desired_outcome);
-
-        Suggestion {
-            opponent: s.opponent,
-            you: action,
-        }
-    }))
+    let suggestions = Day02::parse(input).expect("Failed to parse input");
+
+    Day02::part_two(&suggestions)
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Vec<Suggestion>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines(input).collect()
+    }
+
+    fn part_one(suggestions: &Self::Input) -> Self::PartOne {
+        score(suggestions.iter().copied())
+    }
+
+    fn part_two(suggestions: &Self::Input) -> Self::PartTwo {
+        score(suggestions.iter().map(|s| {
+            // reinterpret the input, flipped because we are considering it from the perspective of
+            // the opponent
+            let desired_outcome = match s.you {
+                Action::Rock => Outcome::Win,
+                Action::Paper => Outcome::Draw,
+                Action::Scissors => Outcome::Lose,
+            };
+
+            let action = s.opponent.desired_outcome(desired_outcome);
+
+            Suggestion {
+                opponent: s.opponent,
+                you: action,
+            }
+        }))
+    }
 }
 
 fn score(suggestions: impl IntoIterator<Item = Suggestion>) -> u64 {
@@ -32,8 +56,8 @@ fn score(suggestions: impl IntoIterator<Item = Suggestion>) -> u64 {
         .sum()
 }
 
-#[derive(Debug)]
-struct Suggestion {
+#[derive(Debug, Copy, Clone)]
+pub struct Suggestion {
     opponent: Action,
     you: Action,
 }
@@ -74,7 +98,7 @@ impl Action {
 
     fn outcome(&self, opponent_action: Self) -> Outcome {
         match (self, opponent_action) {
-            (a @ _, b @ _) if a == &b => Outcome::Draw,
+            (a, b) if a == &b => Outcome::Draw,
             (Self::Rock, Self::Scissors)
             | (Self::Paper, Self::Rock)
             | (Self::Scissors, Self::Paper) => Outcome::Win,
@@ -125,7 +149,7 @@ impl Outcome {
 #[cfg(test)]
 mod tests {
     use super::{star_one, star_two};
-    const TEST_INPUT: &'static str = r#"
+    const TEST_INPUT: &str = r#"
 A Y
 B X
 C Z
@@ -1,6 +1,8 @@
 use lazy_static::lazy_static;
 use std::collections::{HashMap, HashSet};
 
+use crate::{Error, Location, Solution};
+
 lazy_static! {
     static ref PRIORITIES: HashMap<char, u64> = {
         let lowercase = ('a'..='z').enumerate().map(|(i, c)| (c, i as u64 + 1));
@@ -11,44 +13,108 @@ lazy_static! {
 }
 
 pub fn star_one(input: &str) -> u64 {
-    input
-        .lines()
-        .map(str::trim)
-        .filter(|l| l.len() > 0)
-        .map(|l| {
-            let (left, right) = l.split_at(l.len() / 2);
-            let left_chars = to_chars(left);
-            let right_chars = to_chars(right);
-            let intersection = left_chars.intersection(&right_chars);
-
-            intersection.map(priority).sum::<u64>()
-        })
-        .sum()
+    let rucksacks = Day03::parse(input).expect("Failed to parse input");
+
+    Day03::part_one(&rucksacks)
 }
 
 pub fn star_two(input: &str) -> u64 {
-    let sacks: Vec<_> = input
-        .lines()
-        .map(str::trim)
-        .filter(|l| l.len() > 0)
-        .map(to_chars)
-        .collect();
-
-    sacks
-        .chunks(3)
-        .map(|group| {
-            let mut in_common = group[0].clone();
-
-            for g in &group[1..] {
-                in_common = in_common.intersection(g).copied().collect();
+    let rucksacks = Day03::parse(input).expect("Failed to parse input");
+
+    Day03::part_two(&rucksacks)
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Vec<String>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let lines: Vec<&str> = input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        for line in &lines {
+            if let Some((i, c)) = line
+                .char_indices()
+                .find(|(_, c)| !PRIORITIES.contains_key(c))
+            {
+                return Err(
+                    Error::parse(line, &line[i..i + c.len_utf8()], "Invalid item")
+                        .offset(Location::of(input, line), line),
+                );
             }
+        }
 
-            assert!(in_common.len() == 1);
-            let in_common = in_common.drain().next().unwrap();
+        if !lines.len().is_multiple_of(3) {
+            return Err(format!(
+                "Expected the rucksacks to form groups of three, got {} rucksacks",
+                lines.len()
+            )
+            .into());
+        }
 
-            priority(&in_common)
-        })
-        .sum()
+        for (i, group) in lines.chunks(3).enumerate() {
+            let sacks: Vec<_> = group.iter().map(|l| to_chars(l)).collect();
+
+            if badge(&sacks).is_none() {
+                return Err(format!(
+                    "Expected group {} to have exactly one item in common",
+                    i + 1
+                )
+                .into());
+            }
+        }
+
+        Ok(lines.into_iter().map(ToOwned::to_owned).collect())
+    }
+
+    fn part_one(rucksacks: &Self::Input) -> Self::PartOne {
+        rucksacks
+            .iter()
+            .map(|l| {
+                let (left, right) = l.split_at(l.len() / 2);
+                let left_chars = to_chars(left);
+                let right_chars = to_chars(right);
+                let intersection = left_chars.intersection(&right_chars);
+
+                intersection.map(priority).sum::<u64>()
+            })
+            .sum()
+    }
+
+    fn part_two(rucksacks: &Self::Input) -> Self::PartTwo {
+        let sacks: Vec<_> = rucksacks.iter().map(|l| to_chars(l)).collect();
+
+        sacks
+            .chunks(3)
+            .map(|group| {
+                let badge = badge(group).expect("Groups are validated while parsing");
+
+                priority(&badge)
+            })
+            .sum()
+    }
+}
+
+/// The single item carried by every elf in a group, if there is exactly one.
+fn badge(group: &[HashSet<char>]) -> Option<char> {
+    let (first, rest) = group.split_first()?;
+    let mut in_common = first.clone();
+
+    for g in rest {
+        in_common = in_common.intersection(g).copied().collect();
+    }
+
+    if in_common.len() == 1 {
+        in_common.into_iter().next()
+    } else {
+        None
+    }
 }
 
 fn to_chars(s: &str) -> HashSet<char> {
@@ -57,15 +123,16 @@ fn to_chars(s: &str) -> HashSet<char> {
 
 fn priority(c: &char) -> u64 {
     *PRIORITIES
-        .get(&c)
+        .get(c)
         .unwrap_or_else(|| panic!("No priority for {}", c))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day03};
+    use crate::{Error, Location, Solution};
 
-    const TEST: &'static str = r#"
+    const TEST: &str = r#"
 vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
@@ -83,4 +150,29 @@ CrZsJsPPZsGzwwsLwLmpwMDw
     fn test_star_two() {
         assert_eq!(star_two(TEST), 70);
     }
+
+    #[test]
+    fn test_parse_incomplete_group() {
+        let input = TEST.lines().take(5).collect::<Vec<_>>().join("\n");
+
+        assert!(matches!(Day03::parse(&input), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn test_parse_ambiguous_badge() {
+        let input = "abcd\nabef\nabgh\n";
+
+        assert!(matches!(Day03::parse(input), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn test_parse_invalid_item() {
+        let result = Day03::parse("abcd\nab1f\nagha\n");
+
+        let Err(Error::Parse(e)) = result else {
+            panic!("Expected a parse error, got {:?}", result);
+        };
+        assert_eq!(e.location, Location::new(2, 3));
+        assert_eq!(e.text, "1");
+    }
 }
@@ -1,23 +1,43 @@
 use std::ops::RangeInclusive;
 
+use crate::{Error, Solution};
+
 pub fn star_one(input: &str) -> usize {
-    parse(input)
-        .filter(|parsed| {
-            let (lhs, rhs) = parsed.as_ref().expect("To be able to parse all lines");
+    let assignments = Day04::parse(input).expect("To be able to parse all lines");
 
-            either_fully_contains(lhs, rhs)
-        })
-        .count()
+    Day04::part_one(&assignments)
 }
 
 pub fn star_two(input: &str) -> usize {
-    parse(input)
-        .filter(|parsed| {
-            let (lhs, rhs) = parsed.as_ref().expect("To be able to parse all lines");
+    let assignments = Day04::parse(input).expect("To be able to parse all lines");
 
-            lhs.intersects(rhs) || rhs.intersects(lhs)
-        })
-        .count()
+    Day04::part_two(&assignments)
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Vec<(RangeInclusive<u64>, RangeInclusive<u64>)>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse(input).collect::<Result<_, String>>()?)
+    }
+
+    fn part_one(assignments: &Self::Input) -> Self::PartOne {
+        assignments
+            .iter()
+            .filter(|(lhs, rhs)| either_fully_contains(lhs, rhs))
+            .count()
+    }
+
+    fn part_two(assignments: &Self::Input) -> Self::PartTwo {
+        assignments
+            .iter()
+            .filter(|(lhs, rhs)| lhs.intersects(rhs) || rhs.intersects(lhs))
+            .count()
+    }
 }
 
 fn parse(
@@ -92,7 +112,7 @@ fn either_fully_contains<Idx: PartialOrd>(
 #[cfg(test)]
 mod tests {
     use super::{star_one, star_two};
-    const TEST: &'static str = r#"
+    const TEST: &str = r#"
 2-4,6-8
 2-3,4-5
 5-7,7-9
@@ -1,47 +1,68 @@
 use std::collections::{HashMap, VecDeque};
 use std::str::FromStr;
 
-use crate::parse_lines;
+use crate::{try_parse_lines, Error, Location, Solution};
 
 pub fn star_one(input: &str) -> String {
-    solve(input, Kind::CrateMover9000)
+    let procedure = Day05::parse(input).expect("Failed to parse input");
+
+    Day05::part_one(&procedure)
 }
 
 pub fn star_two(input: &str) -> String {
-    solve(input, Kind::CrateMover9001)
+    let procedure = Day05::parse(input).expect("Failed to parse input");
+
+    Day05::part_two(&procedure)
 }
 
-fn solve(input: &str, kind: Kind) -> String {
-    let (mut state, commands) = parse(input, kind);
+pub struct Day05;
 
-    for command in commands {
-        state.apply(command);
+impl Solution for Day05 {
+    /// The starting stacks and the rearrangement procedure.
+    type Input = (State, Vec<Command>);
+    type PartOne = String;
+    type PartTwo = String;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let (initial_state, commands) = input.split_once("\n\n").ok_or_else(|| {
+            "Input should contain two groups separated by two new lines".to_owned()
+        })?;
+        let state: State = initial_state.parse()?;
+        let commands = try_parse_lines(commands)
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.offset(Location::of(input, commands), commands))?;
+
+        Ok((state, commands))
     }
 
-    state.top_of_stacks()
+    fn part_one((state, commands): &Self::Input) -> Self::PartOne {
+        solve(state, commands, Kind::CrateMover9000)
+    }
+
+    fn part_two((state, commands): &Self::Input) -> Self::PartTwo {
+        solve(state, commands, Kind::CrateMover9001)
+    }
 }
 
-fn parse(input: &str, kind: Kind) -> (State, Vec<Command>) {
-    let (initial_state, commands) = input
-        .split_once("\n\n")
-        .expect("Input should contain two groups separated by two new lines");
-    let mut state: State = initial_state
-        .parse()
-        .expect("Failed to create state from input");
+fn solve(state: &State, commands: &[Command], kind: Kind) -> String {
+    let mut state = state.clone();
     state.kind = kind;
-    let commands = parse_lines(commands).collect();
 
-    (state, commands)
+    for command in commands {
+        state.apply(*command);
+    }
+
+    state.top_of_stacks()
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Kind {
     CrateMover9000,
     CrateMover9001,
 }
 
-#[derive(Debug)]
-struct State {
+#[derive(Debug, Clone)]
+pub struct State {
     stacks: HashMap<usize, VecDeque<char>>,
     kind: Kind,
 }
@@ -60,7 +81,7 @@ impl State {
                 .stacks
                 .get_mut(&command.destination)
                 .unwrap()
-                .extend(items.into_iter()),
+                .extend(items),
         };
     }
 
@@ -77,8 +98,7 @@ impl FromStr for State {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let last_line = s
             .lines()
-            .rev()
-            .next()
+            .next_back()
             .ok_or_else(|| String::from("State definition has no lines"))?;
         let indices: HashMap<usize, usize> = last_line
             .chars()
@@ -111,49 +131,51 @@ impl FromStr for State {
 }
 
 #[derive(Debug, Copy, Clone)]
-struct Command {
+pub struct Command {
     destination: usize,
     source: usize,
     count: usize,
 }
 
 impl FromStr for Command {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Who needs regex, pfft
-        let rest = s.strip_prefix("move").ok_or_else(|| make_error(s))?;
+        let rest = s
+            .strip_prefix("move")
+            .ok_or_else(|| Error::parse(s, s, "Expected `move`"))?;
 
         let (count, rest) = rest
             .trim_start()
             .split_once(char::is_whitespace)
-            .ok_or_else(|| make_error(rest))?;
+            .ok_or_else(|| make_error(s, rest))?;
         let count: usize = count
             .parse()
-            .map_err(|e| format!("Invalid count {} in {}, err: {}", count, s, e))?;
+            .map_err(|e| Error::parse(s, count, format!("Invalid count: {}", e)))?;
 
         let rest = rest
             .trim_start()
             .strip_prefix("from")
-            .ok_or_else(|| make_error(s))?;
+            .ok_or_else(|| Error::parse(s, rest.trim_start(), "Expected `from`"))?;
 
         let (source, rest) = rest
             .trim_start()
             .split_once(char::is_whitespace)
-            .ok_or_else(|| make_error(s))?;
+            .ok_or_else(|| make_error(s, rest))?;
         let source: usize = source
             .parse()
-            .map_err(|e| format!("Invalid source {} in {}, err: {}", source, s, e))?;
+            .map_err(|e| Error::parse(s, source, format!("Invalid source: {}", e)))?;
 
         let rest = rest
             .trim_start()
             .strip_prefix("to")
-            .ok_or_else(|| make_error(s))?;
+            .ok_or_else(|| Error::parse(s, rest.trim_start(), "Expected `to`"))?;
 
-        let destination: usize = rest
-            .trim()
+        let destination = rest.trim();
+        let destination: usize = destination
             .parse()
-            .map_err(|e| format!("Invalid destination {} in {}, err: {}", source, s, e))?;
+            .map_err(|e| Error::parse(s, destination, format!("Invalid destination: {}", e)))?;
 
         Ok(Self {
             source,
@@ -163,15 +185,16 @@ impl FromStr for Command {
     }
 }
 
-fn make_error(s: &str) -> String {
-    format!("Invalid command `{}`", s)
+fn make_error(s: &str, rest: &str) -> Error {
+    Error::parse(s, rest.trim_start(), "Incomplete command")
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day05};
+    use crate::{Error, Location, Solution};
 
-    const INPUT: &'static str = r#"
+    const INPUT: &str = r#"
     [D]    
 [N] [C]    
 [Z] [M] [P]
@@ -192,4 +215,15 @@ move 1 from 1 to 2
     fn test_star_two() {
         assert_eq!(star_two(INPUT), "MCD");
     }
+
+    #[test]
+    fn test_parse_error_location() {
+        let input = INPUT.replace("move 2 from 2 to 1", "move 2 from two to 1");
+
+        let Err(Error::Parse(e)) = Day05::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(9, 13));
+        assert_eq!(e.text, "two");
+    }
 }
@@ -1,25 +1,59 @@
+use crate::{Error, Solution};
+
 pub fn star_one(input: &str) -> usize {
-    let mat = input
-        .trim()
-        .as_bytes() // Assume ascii
-        .windows(4)
-        .enumerate()
-        .find(|(_, w)| all_different(&w))
-        .unwrap();
+    let signal = Day06::parse(input).expect("Failed to parse input");
 
-    mat.0 + 4
+    Day06::part_one(&signal)
 }
 
 pub fn star_two(input: &str) -> usize {
-    let mat = input
-        .trim()
-        .as_bytes() // Assume ascii
-        .windows(14)
+    let signal = Day06::parse(input).expect("Failed to parse input");
+
+    Day06::part_two(&signal)
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    /// The datastream buffer, assumed to be ascii.
+    type Input = Vec<u8>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let signal = input.trim().as_bytes().to_vec();
+
+        // Any window of 14 different characters starts with 4 different ones, so this covers
+        // both parts.
+        if find_marker(&signal, MESSAGE_MARKER).is_none() {
+            return Err(format!(
+                "Expected the signal to contain {MESSAGE_MARKER} consecutive different characters"
+            )
+            .into());
+        }
+
+        Ok(signal)
+    }
+
+    fn part_one(signal: &Self::Input) -> Self::PartOne {
+        find_marker(signal, PACKET_MARKER).expect("Markers are validated while parsing")
+    }
+
+    fn part_two(signal: &Self::Input) -> Self::PartTwo {
+        find_marker(signal, MESSAGE_MARKER).expect("Markers are validated while parsing")
+    }
+}
+
+const PACKET_MARKER: usize = 4;
+const MESSAGE_MARKER: usize = 14;
+
+fn find_marker(signal: &[u8], length: usize) -> Option<usize> {
+    let (start, _) = signal
+        .windows(length)
         .enumerate()
-        .find(|(_, w)| all_different(&w))
-        .unwrap();
+        .find(|(_, w)| all_different(w))?;
 
-    mat.0 + 14
+    Some(start + length)
 }
 
 fn all_different<T: Eq>(values: &[T]) -> bool {
@@ -36,7 +70,8 @@ fn all_different<T: Eq>(values: &[T]) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{all_different, star_one, star_two};
+    use super::{all_different, star_one, star_two, Day06};
+    use crate::{Error, Solution};
 
     const STAR_ONE_TEST_CASES: &[(&str, usize)] = &[
         ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7),
@@ -73,4 +108,13 @@ mod tests {
         assert!(!all_different(b"bvwb"));
         assert!(all_different(b"jplb"));
     }
+
+    #[test]
+    fn test_parse_without_marker() {
+        assert!(matches!(
+            Day06::parse("abcdabcdabcdabcd"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(Day06::parse("abc"), Err(Error::Invalid(_))));
+    }
 }
@@ -5,31 +5,53 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
+use crate::{Error, Location, Solution};
+
 pub fn star_one(input: &str) -> u64 {
-    let file_system: FileSystem = input.parse().expect("Failed to parse file system");
+    let file_system = Day07::parse(input).expect("Failed to parse file system");
 
-    let sizes = file_system.calculate_sizes();
-    sizes.values().filter(|&&s| s <= 100000).sum()
+    Day07::part_one(&file_system)
 }
 
 pub fn star_two(input: &str) -> u64 {
-    let file_system: FileSystem = input.parse().expect("Failed to parse file system");
+    let file_system = Day07::parse(input).expect("Failed to parse file system");
+
+    Day07::part_two(&file_system)
+}
 
-    let sizes = file_system.calculate_sizes();
-    let root_path = file_system.root_path();
-    let free_space = 70_000_000 - sizes[&root_path];
-    let need_to_free = 30_000_000 - free_space;
+pub struct Day07;
 
-    sizes
-        .into_iter()
-        .filter(|(p, s)| p != &root_path && s >= &need_to_free)
-        .map(|(_, s)| s)
-        .min()
-        .unwrap()
+impl Solution for Day07 {
+    type Input = FileSystem;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part_one(file_system: &Self::Input) -> Self::PartOne {
+        let sizes = file_system.calculate_sizes();
+        sizes.values().filter(|&&s| s <= 100000).sum()
+    }
+
+    fn part_two(file_system: &Self::Input) -> Self::PartTwo {
+        let sizes = file_system.calculate_sizes();
+        let root_path = file_system.root_path();
+        let free_space = 70_000_000 - sizes[&root_path];
+        let need_to_free = 30_000_000 - free_space;
+
+        sizes
+            .into_iter()
+            .filter(|(p, s)| p != &root_path && s >= &need_to_free)
+            .map(|(_, s)| s)
+            .min()
+            .unwrap()
+    }
 }
 
 #[derive(Debug)]
-struct FileSystem {
+pub struct FileSystem {
     root_idx: Idx,
     arena: Arena<Entry>,
 }
@@ -47,7 +69,7 @@ impl FileSystem {
             let entry = &self.arena[idx];
 
             // Visit
-            let control = visit(&entry, &path, depth);
+            let control = visit(entry, &path, depth);
             if control.is_break() {
                 return control;
             }
@@ -74,19 +96,16 @@ impl FileSystem {
     fn calculate_sizes(self: &FileSystem) -> HashMap<Path, u64> {
         let mut sizes: HashMap<Path, u64> = Default::default();
         self.walk::<_, (), ()>(|e, path, _| {
-            if e.is_dir() && !sizes.contains_key(&path) {
+            if e.is_dir() && !sizes.contains_key(path) {
                 sizes.insert(path.clone(), 0);
             }
 
-            match e {
-                Entry::File { size, .. } => {
-                    for (seen_path, seen_size) in sizes.iter_mut() {
-                        if path.is_sub_path(seen_path) {
-                            *seen_size += size;
-                        }
+            if let Entry::File { size, .. } = e {
+                for (seen_path, seen_size) in sizes.iter_mut() {
+                    if path.is_sub_path(seen_path) {
+                        *seen_size += size;
                     }
                 }
-                _ => {}
             }
 
             ControlFlow::Continue(())
@@ -97,7 +116,7 @@ impl FileSystem {
 }
 
 impl FromStr for FileSystem {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut arena: Arena<Entry> = Arena::new();
@@ -107,7 +126,7 @@ impl FromStr for FileSystem {
 
         while let Some(line) = lines.next() {
             let Some(rest) = line.strip_prefix('$') else {
-                return Err(format!("Unexpected line `{}` was not a command", line));
+                return Err(Error::parse(s, line, "Expected a command"));
             };
             let rest = rest.trim();
             let mut parts = rest.split_whitespace();
@@ -118,7 +137,7 @@ impl FromStr for FileSystem {
                 (Some("ls"), None) => {
                     let ls_output = lines.take_while_ref(|l| !l.starts_with('$'));
 
-                    let children = parse_ls_output(ls_output, &mut arena, current_idx)?;
+                    let children = parse_ls_output(s, ls_output, &mut arena, current_idx)?;
 
                     arena[current_idx].set_children(children);
                 }
@@ -134,10 +153,10 @@ impl FromStr for FileSystem {
                             &arena,
                             |e| matches!(e, Entry::Dir { name, .. } if name == arg),
                         )
-                        .ok_or_else(|| format!("Invalid cd to {}, dir missing", arg))?;
+                        .ok_or_else(|| Error::parse(s, arg, "Invalid cd, dir missing"))?;
                     current_idx = dir_idx;
                 }
-                _ => return Err(format!(r#"Invalid command "{}""#, line)),
+                _ => return Err(Error::parse(s, line, "Invalid command")),
             }
         }
 
@@ -171,13 +190,14 @@ impl Path {
 }
 
 fn parse_ls_output<'a>(
+    input: &str,
     lines: impl IntoIterator<Item = &'a str>,
     arena: &mut Arena<Entry>,
     parent_idx: Idx,
-) -> Result<Vec<Idx>, String> {
+) -> Result<Vec<Idx>, Error> {
     lines
         .into_iter()
-        .map(|l| Entry::parse(l, parent_idx))
+        .map(|l| Entry::parse(l, parent_idx).map_err(|e| e.offset(Location::of(input, l), l)))
         .map(|r| r.map(|e| arena.insert(e)))
         .collect()
 }
@@ -228,7 +248,7 @@ impl Entry {
         }
     }
 
-    fn parse(s: &str, parent: Idx) -> Result<Self, String> {
+    fn parse(s: &str, parent: Idx) -> Result<Self, Error> {
         // Dir
         if let Some(name) = s.strip_prefix("dir") {
             return Ok(Self::Dir {
@@ -241,10 +261,10 @@ impl Entry {
         // Must be a file then
         let (size, name) = s
             .split_once(char::is_whitespace)
-            .ok_or_else(|| format!("Invalid ls output {}", s))?;
+            .ok_or_else(|| Error::parse(s, s, "Invalid ls output"))?;
         let size = size
             .parse()
-            .map_err(|e| format!("Failed to parse file size in {}, {}", s, e))?;
+            .map_err(|e| Error::parse(s, size, format!("Failed to parse file size: {}", e)))?;
         let name = name.trim().to_owned();
 
         Ok(Self::File { size, name })
@@ -305,7 +325,7 @@ impl fmt::Display for FileSystem {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let ControlFlow::Break(e) =
             self.walk(
-                |e, _, depth| match write!(f, "{:>depth$}{}\n", "", e, depth = depth * 2) {
+                |e, _, depth| match writeln!(f, "{:>depth$}{}", "", e, depth = depth * 2) {
                     Err(e) => ControlFlow::Break(e),
                     Ok(_) => ControlFlow::Continue(()),
                 },
@@ -322,7 +342,7 @@ impl fmt::Display for FileSystem {
 mod tests {
     use super::{star_one, star_two};
 
-    const TEST_INPUT: &'static str = r#"
+    const TEST_INPUT: &str = r#"
 $ cd /
 $ ls
 dir a
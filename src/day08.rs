@@ -2,91 +2,92 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
+use crate::{grid::Grid, math::Vector2, Error, Solution};
+
 pub fn star_one(input: &str) -> usize {
-    let grid = parse(input);
-
-    let visible: HashSet<_> = (0..grid.height())
-        .flat_map(|y| {
-            grid.visible_trees(Direction::Left { y })
-                .chain(grid.visible_trees(Direction::Right { y }))
-        })
-        .chain((0..grid.width()).flat_map(|x| {
-            grid.visible_trees(Direction::Up { x })
-                .chain(grid.visible_trees(Direction::Down { x }))
-        }))
-        .collect();
-
-    visible.len()
+    let forest = Day08::parse(input).expect("Failed to parse input");
+
+    Day08::part_one(&forest)
 }
 
 pub fn star_two(input: &str) -> usize {
-    let grid = parse(input);
+    let forest = Day08::parse(input).expect("Failed to parse input");
+
+    Day08::part_two(&forest)
+}
 
-    let grid = &grid;
-    // BRUUUUUTE FORCE!
-    (0..grid.height())
-        .flat_map(|y| {
-            (0..grid.width()).map(move |x| {
-                let height = grid.grid[y][x];
+pub struct Day08;
 
-                let up_score = grid.scenic_score(Direction::Up { x }, y, height);
-                let down_score = grid.scenic_score(Direction::Down { x }, y, height);
+impl Solution for Day08 {
+    type Input = Forest;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-                let left_score = grid.scenic_score(Direction::Left { y }, x, height);
-                let right_score = grid.scenic_score(Direction::Right { y }, x, height);
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+
+    fn part_one(forest: &Self::Input) -> Self::PartOne {
+        let visible: HashSet<_> = (0..forest.height())
+            .flat_map(|y| {
+                forest
+                    .visible_trees(Direction::Left { y })
+                    .chain(forest.visible_trees(Direction::Right { y }))
+            })
+            .chain((0..forest.width()).flat_map(|x| {
+                forest
+                    .visible_trees(Direction::Up { x })
+                    .chain(forest.visible_trees(Direction::Down { x }))
+            }))
+            .collect();
+
+        visible.len()
+    }
+
+    fn part_two(forest: &Self::Input) -> Self::PartTwo {
+        // BRUUUUUTE FORCE!
+        forest
+            .trees
+            .iter()
+            .map(|(position, &height)| {
+                let (x, y) = (position.x as usize, position.y as usize);
+
+                let up_score = forest.scenic_score(Direction::Up { x }, y, height);
+                let down_score = forest.scenic_score(Direction::Down { x }, y, height);
+
+                let left_score = forest.scenic_score(Direction::Left { y }, x, height);
+                let right_score = forest.scenic_score(Direction::Right { y }, x, height);
 
                 up_score * down_score * left_score * right_score
             })
-        })
-        .max()
-        .unwrap()
+            .max()
+            .unwrap()
+    }
 }
 
-fn parse(input: &str) -> Grid {
-    let grid = input
-        .lines()
-        .map(str::trim)
-        .filter(|l| !l.is_empty())
-        .map(|line| {
-            line.chars()
-                .map(|c| {
-                    c.to_digit(10)
-                        .expect("All trees should be represented by numbers between 0 and 9")
-                        as u8
-                })
-                .collect()
-        })
-        .collect();
-
-    Grid { grid }
+fn parse(input: &str) -> Result<Forest, Error> {
+    let trees = Grid::parse(input, |c| c.to_digit(10).map(|d| d as u8))?;
+
+    Ok(Forest { trees })
 }
 
 #[derive(Debug)]
-struct Grid {
-    grid: Vec<Vec<u8>>,
+pub struct Forest {
+    trees: Grid<u8>,
 }
 
-impl Grid {
-    /// Scan the grid.
+impl Forest {
+    /// Scan the forest.
     fn scan(
         &self,
         direction: Direction,
         dim_start: Option<usize>,
     ) -> impl Iterator<Item = (u8, (usize, usize))> + '_ {
-        let range: Box<dyn Iterator<Item = (usize, usize)>> = match direction {
-            d @ (Direction::Up { x } | Direction::Down { x }) => {
-                Box::new(d.range(self.height(), dim_start).map(move |y| (x, y)))
-            }
-            d @ (Direction::Left { y } | Direction::Right { y }) => {
-                Box::new(d.range(self.width(), dim_start).map(move |x| (x, y)))
-            }
-        };
-
-        range.map(move |(x, y)| {
-            let height = self.grid[y][x];
-
-            (height, (x, y))
-        })
+        let (from, step) = direction.ray(self.width(), self.height(), dim_start);
+
+        self.trees
+            .ray(from, step)
+            .map(|(p, &height)| (height, (p.x as usize, p.y as usize)))
     }
 
     fn visible_trees(&self, direction: Direction) -> impl Iterator<Item = (usize, usize)> + '_ {
@@ -101,11 +102,11 @@ impl Grid {
     }
 
     fn width(&self) -> usize {
-        self.grid[0].len()
+        self.trees.width()
     }
 
     fn height(&self) -> usize {
-        self.grid.len()
+        self.trees.height()
     }
 
     fn scenic_score(&self, direction: Direction, dim: usize, height: u8) -> usize {
@@ -132,12 +133,22 @@ enum Direction {
 }
 
 impl Direction {
-    fn range(&self, max_dim: usize, start_dim: Option<usize>) -> Box<dyn Iterator<Item = usize>> {
-        match self {
-            Direction::Up { .. } => Box::new((0..(start_dim.unwrap_or(max_dim))).rev()),
-            Direction::Down { .. } => Box::new(start_dim.map(|d| d + 1).unwrap_or(0)..max_dim),
-            Direction::Left { .. } => Box::new((0..(start_dim.unwrap_or(max_dim))).rev()),
-            Direction::Right { .. } => Box::new(start_dim.map(|d| d + 1).unwrap_or(0)..max_dim),
+    /// Where to start a ray scanning in this direction and the step to take.
+    fn ray(
+        &self,
+        width: usize,
+        height: usize,
+        start_dim: Option<usize>,
+    ) -> (Vector2<isize>, Vector2<isize>) {
+        // Scanning starts just before the first or just after the last cell of the line
+        let before = start_dim.map_or(-1, |d| d as isize);
+        let after = |len: usize| start_dim.unwrap_or(len) as isize;
+
+        match *self {
+            Direction::Up { x } => (Vector2::new(x as isize, after(height)), Vector2::new(0, -1)),
+            Direction::Down { x } => (Vector2::new(x as isize, before), Vector2::new(0, 1)),
+            Direction::Left { y } => (Vector2::new(after(width), y as isize), Vector2::new(-1, 0)),
+            Direction::Right { y } => (Vector2::new(before, y as isize), Vector2::new(1, 0)),
         }
     }
 }
@@ -147,7 +158,7 @@ mod tests {
     use crate::day08::Direction;
 
     use super::{parse, star_one, star_two};
-    const INPUT: &'static str = r#"
+    const INPUT: &str = r#"
 30373
 25512
 65332
@@ -167,7 +178,7 @@ mod tests {
 
     #[test]
     fn test_scan_with_starting_point_up() {
-        let grid = parse(INPUT);
+        let grid = parse(INPUT).unwrap();
 
         let result: Vec<_> = grid.scan(Direction::Up { x: 2 }, Some(4)).collect();
         let expected = vec![(5, (2, 3)), (3, (2, 2)), (5, (2, 1)), (3, (2, 0))];
@@ -180,7 +191,7 @@ mod tests {
 
     #[test]
     fn test_scan_with_starting_point_left() {
-        let grid = parse(INPUT);
+        let grid = parse(INPUT).unwrap();
 
         let result: Vec<_> = grid.scan(Direction::Left { y: 1 }, Some(3)).collect();
         let expected = vec![(5, (2, 1)), (5, (1, 1)), (2, (0, 1))];
@@ -193,7 +204,7 @@ mod tests {
 
     #[test]
     fn test_scan_with_starting_point_down() {
-        let grid = parse(INPUT);
+        let grid = parse(INPUT).unwrap();
 
         let result: Vec<_> = grid.scan(Direction::Down { x: 2 }, Some(3)).collect();
         let expected = vec![(3, (2, 4))];
@@ -208,7 +219,7 @@ mod tests {
 
     #[test]
     fn test_scan_with_starting_point_right() {
-        let grid = parse(INPUT);
+        let grid = parse(INPUT).unwrap();
 
         let result: Vec<_> = grid.scan(Direction::Right { y: 1 }, Some(3)).collect();
         let expected = vec![(2, (4, 1))];
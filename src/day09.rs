@@ -1,22 +1,45 @@
 use std::{collections::HashSet, fmt, str::FromStr};
 
-use crate::{math::Vector2, parse_lines};
+use crate::{math::Vector2, try_parse_lines, Error, Location, Solution};
 
 pub fn star_one(input: &str) -> usize {
-    solve(input, 1)
+    let motions = Day09::parse(input).expect("Failed to parse input");
+
+    Day09::part_one(&motions)
 }
 
 pub fn star_two(input: &str) -> usize {
-    solve(input, 9)
+    let motions = Day09::parse(input).expect("Failed to parse input");
+
+    Day09::part_two(&motions)
 }
 
-fn solve(input: &str, tail_length: usize) -> usize {
-    let motions: Vec<_> = parse_lines::<Motion>(input).collect();
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<Motion>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines(input).collect()
+    }
+
+    fn part_one(motions: &Self::Input) -> Self::PartOne {
+        solve(motions, 1)
+    }
+
+    fn part_two(motions: &Self::Input) -> Self::PartTwo {
+        solve(motions, 9)
+    }
+}
+
+fn solve(motions: &[Motion], tail_length: usize) -> usize {
     let mut state = State::new(tail_length);
 
     let mut tail_locations = HashSet::new();
     for motion in motions {
-        state.apply(&motion, |s| {
+        state.apply(motion, |s| {
             tail_locations.insert(*s.tail.last().unwrap());
         });
     }
@@ -51,33 +74,20 @@ impl State {
     }
 
     fn step_once(&mut self, direction: Direction) {
-        self.head = self.head + direction.into();
+        self.head += direction.into();
 
         for i in 0..self.tail.len() {
-            let ahead = if i == 0 {
-                &self.head
-            } else {
-                &self.tail[i - 1]
-            };
-            if ahead.is_adjacent(&self.tail[i]) {
+            let ahead = if i == 0 { self.head } else { self.tail[i - 1] };
+            if ahead.chebyshev_distance(self.tail[i]) <= 1 {
                 continue;
             }
 
-            let dir = *ahead - self.tail[i];
-            self.tail[i] =
-                Vector2::new(self.tail[i].x + clamp(dir.x), self.tail[i].y + clamp(dir.y))
+            let step = (ahead - self.tail[i]).signum();
+            self.tail[i] += step;
         }
     }
 }
 
-fn clamp(v: i64) -> i64 {
-    if v < 0 {
-        v.clamp(-1, 0)
-    } else {
-        v.clamp(0, 1)
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 enum Direction {
     Left,
@@ -98,60 +108,58 @@ impl From<Direction> for Vector2<i64> {
 }
 
 impl FromStr for Direction {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let first = s
             .trim()
             .chars()
             .next()
-            .ok_or_else(|| format!("Couldn't parse direction from {}", s))?;
+            .ok_or_else(|| Error::parse(s, s, "Missing direction"))?;
 
         match first {
             'L' => Ok(Self::Left),
             'R' => Ok(Self::Right),
             'U' => Ok(Self::Up),
             'D' => Ok(Self::Down),
-            _ => Err(format!("Invalid direction {} in {}", first, s)),
+            _ => Err(Error::parse(
+                s,
+                s.trim(),
+                format!("Invalid direction {}", first),
+            )),
         }
     }
 }
 
 #[derive(Debug)]
-struct Motion {
+pub struct Motion {
     direction: Direction,
     steps: usize,
 }
 
 impl FromStr for Motion {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
         let (s1, s2) = s
             .split_once(char::is_whitespace)
-            .ok_or_else(|| format!("Invalid motion {}", s))?;
-        let direction = s1.parse()?;
+            .ok_or_else(|| Error::parse(s, s, "Invalid motion"))?;
+        let direction = s1
+            .parse()
+            .map_err(|e: Error| e.offset(Location::of(s, s1), s1))?;
         let steps = s2
             .parse()
-            .map_err(|e| format!("Failed to parse steps in {}: {}", s, e))?;
+            .map_err(|e| Error::parse(s, s2, format!("Failed to parse steps: {}", e)))?;
 
         Ok(Self { direction, steps })
     }
 }
 
-impl Vector2<i64> {
-    fn is_adjacent(&self, to: &Self) -> bool {
-        let vec = *self - *to;
-
-        (vec.x.pow(2) + vec.y.pow(2)) <= 2
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::{star_one, star_two};
-    const INPUT_STAR_ONE: &'static str = r#"
+    const INPUT_STAR_ONE: &str = r#"
 R 4
 U 4
 L 3
@@ -161,7 +169,7 @@ D 1
 L 5
 R 2"#;
 
-    const INPUT_STAR_TWO: &'static str = r#"
+    const INPUT_STAR_TWO: &str = r#"
 R 5
 U 8
 L 8
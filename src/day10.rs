@@ -2,62 +2,82 @@ use std::{collections::HashMap, fmt::Write, str::FromStr};
 
 use itertools::Itertools;
 
-use crate::parse_lines;
+use crate::{try_parse_lines, Error, Solution};
 
 pub fn star_one(input: &str) -> i64 {
-    let operations: Vec<Op> = parse_lines(input).collect();
-    let mut machine = Machine::new(operations);
-    let target_cycles = vec![20, 60, 100, 140, 180, 220];
-
-    let mut result = 0;
-    for c in 1..=220 {
-        if target_cycles.contains(&c) {
-            let x = machine.register(Register::X);
-            result += x * c;
-        }
-        machine.tick();
-    }
+    let operations = Day10::parse(input).expect("Failed to parse input");
 
-    result
+    Day10::part_one(&operations)
 }
 
 pub fn star_two(input: &str) -> String {
-    let operations: Vec<Op> = parse_lines(input).collect();
-    let mut machine = Machine::new(operations);
-    let display = (0..240).map(|c| {
-        let coord = c % 40;
-        let pixel = match machine.register(Register::X) {
-            x if coord >= (x - 1) && coord <= x + 1 => '#',
-            _ => '.',
-        };
-
-        machine.tick();
-
-        pixel
-    });
-
-    let mut s = String::with_capacity(300);
-    for line in display
-        .chunks(40)
-        .into_iter()
-        .map(|chunk| chunk.collect::<String>())
-    {
-        writeln!(&mut s, "{}", line).expect("Failed to write to string");
+    let operations = Day10::parse(input).expect("Failed to parse input");
+
+    Day10::part_two(&operations)
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Op>;
+    type PartOne = i64;
+    type PartTwo = String;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines(input).collect()
     }
 
-    println!("{s}");
+    fn part_one(operations: &Self::Input) -> Self::PartOne {
+        let mut machine = Machine::new(operations.clone());
+        let target_cycles = [20, 60, 100, 140, 180, 220];
 
-    s
+        let mut result = 0;
+        for c in 1..=220 {
+            if target_cycles.contains(&c) {
+                let x = machine.register(Register::X);
+                result += x * c;
+            }
+            machine.tick();
+        }
+
+        result
+    }
+
+    fn part_two(operations: &Self::Input) -> Self::PartTwo {
+        let mut machine = Machine::new(operations.clone());
+        let display = (0..240).map(|c| {
+            let coord = c % 40;
+            let pixel = match machine.register(Register::X) {
+                x if coord >= (x - 1) && coord <= x + 1 => '#',
+                _ => '.',
+            };
+
+            machine.tick();
+
+            pixel
+        });
+
+        let mut s = String::with_capacity(300);
+        for line in display
+            .chunks(40)
+            .into_iter()
+            .map(|chunk| chunk.collect::<String>())
+        {
+            writeln!(&mut s, "{}", line).expect("Failed to write to string");
+        }
+
+        s
+    }
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-enum Register {
+pub enum Register {
     X,
 }
 
-#[derive(Debug)]
-enum Op {
-    NOOP,
+#[derive(Debug, Copy, Clone)]
+pub enum Op {
+    Noop,
     Add(Register, i64),
 }
 
@@ -65,14 +85,14 @@ impl Op {
     fn cycles(&self) -> u64 {
         use Op::*;
         match self {
-            NOOP => 1,
+            Noop => 1,
             Add(..) => 2,
         }
     }
 }
 
 impl FromStr for Op {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parts = s.split_whitespace();
@@ -80,22 +100,22 @@ impl FromStr for Op {
         let op = parts
             .next()
             .map(str::trim)
-            .ok_or_else(|| format!(r#"No operation in "{s}""#))?;
+            .ok_or_else(|| Error::parse(s, s, "No operation"))?;
 
         match op {
-            "noop" => Ok(Self::NOOP),
+            "noop" => Ok(Self::Noop),
             "addx" => {
                 let arg = parts
                     .next()
                     .map(str::trim)
-                    .ok_or_else(|| format!(r#"Missing argument for addx in "{s}"#))?;
+                    .ok_or_else(|| Error::parse(s, s, "Missing argument for addx"))?;
                 let parsed: i64 = arg
                     .parse()
-                    .map_err(|e| format!(r#"Failed to parse addx argument in "{s}""#))?;
+                    .map_err(|e| Error::parse(s, arg, format!("Invalid addx argument: {e}")))?;
 
                 Ok(Self::Add(Register::X, parsed))
             }
-            _ => Err(format!(r#"Unknown op code `{op}` in "{s}""#)),
+            _ => Err(Error::parse(s, op, format!("Unknown op code `{op}`"))),
         }
     }
 }
@@ -135,7 +155,7 @@ impl Machine {
     fn apply(&mut self) {
         use Op::*;
         match self.operations[self.ip] {
-            NOOP => {}
+            Noop => {}
             Add(reg, value) => *self.register_mut(reg) += value,
         }
     }
@@ -158,7 +178,7 @@ impl Machine {
 mod tests {
     use super::{star_one, star_two};
 
-    const INPUT: &'static str = include_str!("day_10_test.txt");
+    const INPUT: &str = include_str!("day_10_test.txt");
 
     #[test]
     fn test_star_one() {
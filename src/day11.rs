@@ -1,57 +1,585 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::mem;
+use std::ops::Rem;
+use std::str::FromStr;
 
-pub fn star_one(mut monkeys: Vec<Monkey>) -> u64 {
-    solve(monkeys, 20, false)
+use num_bigint::BigUint;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::{try_parse_custom_separated, Error, Location, Solution};
+
+pub fn star_one(input: &str) -> u128 {
+    let monkeys = Day11::parse(input).expect("Failed to parse monkeys");
+
+    Day11::part_one(&monkeys)
 }
 
-pub fn star_two(mut monkeys: Vec<Monkey>) -> u64 {
-    solve(monkeys, 10_000, true)
+pub fn star_two(input: &str) -> u128 {
+    let monkeys = Day11::parse(input).expect("Failed to parse monkeys");
+
+    Day11::part_two(&monkeys)
 }
 
-fn solve(mut monkeys: Vec<Monkey>, rounds: usize, worried: bool) -> u64 {
-    let mut inspect_counts: HashMap<usize, u64> = HashMap::new();
+pub struct Day11;
 
-    for _ in 1..=rounds {
-        do_round(&mut monkeys, worried, |monkey| {
-            *inspect_counts.entry(monkey).or_default() += 1;
-        });
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+    type PartOne = u128;
+    type PartTwo = u128;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let monkeys: Vec<Monkey> =
+            try_parse_custom_separated(input, "\n\n").collect::<Result<_, _>>()?;
+
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            if monkey.id != idx {
+                return Err(format!("Expected monkey {idx}, found monkey {}", monkey.id).into());
+            }
+
+            if monkey.test.operand == 0 {
+                return Err(format!("Monkey {} tests for divisibility by 0", monkey.id).into());
+            }
+
+            for target in [monkey.test.true_target, monkey.test.false_target] {
+                if target >= monkeys.len() {
+                    return Err(format!(
+                        "Monkey {} throws to monkey {target}, which doesn't exist",
+                        monkey.id
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(monkeys)
     }
 
-    let sorted = {
-        let mut v: Vec<_> = inspect_counts.into_values().collect();
-        v.sort_by(|a, b| b.cmp(a));
+    fn part_one(monkeys: &Self::Input) -> Self::PartOne {
+        let counts = inspections(monkeys, 20, false).expect("Failed to simulate the monkeys");
+
+        InspectionReport::new(20, counts).monkey_business()
+    }
+
+    fn part_two(monkeys: &Self::Input) -> Self::PartTwo {
+        simulate(monkeys, 10_000)
+            .expect("Failed to simulate the monkeys")
+            .monkey_business()
+    }
+}
+
+/// How many items each monkey inspected during a simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectionReport {
+    pub rounds: u64,
+    /// Number of inspections, indexed by monkey id.
+    pub counts: Vec<u64>,
+}
+
+impl InspectionReport {
+    pub fn new(rounds: u64, counts: Vec<u64>) -> Self {
+        Self { rounds, counts }
+    }
+
+    /// The product of the two largest inspection counts.
+    pub fn monkey_business(&self) -> u128 {
+        let mut sorted = self.counts.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+
+        sorted.into_iter().take(2).map(u128::from).product()
+    }
+}
+
+/// Simulate `rounds` rounds without relief, i.e. worry levels are never divided by 3.
+///
+/// Items never interact, so each one is followed on its own. As only its worry level modulo
+/// the divisors matters, an item eventually revisits a (monkey, worry level) state. From there
+/// on it repeats the same cycle of inspections, which is extrapolated up to `rounds`. This makes
+/// the running time independent of `rounds` once every item's cycle has been found.
+///
+/// That only holds while the operations survive the modular reduction. When one divides, the
+/// worry levels are kept exact and every round is simulated, see [`inspections`]. Exact worry
+/// levels can grow without bound, e.g. with `old * old / 2`, so those simulations give up once
+/// a worry level needs more than [`MAX_EXACT_BITS`] bits.
+///
+/// Fails if a worry level becomes negative, is divided by zero or grows past that limit.
+pub fn simulate(monkeys: &[Monkey], rounds: u64) -> Result<InspectionReport, Error> {
+    if has_division(monkeys) {
+        return Ok(InspectionReport::new(
+            rounds,
+            inspections(monkeys, rounds, true)?,
+        ));
+    }
 
-        v
+    let counts = widening(
+        Arithmetic::select(monkeys, true),
+        |arithmetic| match arithmetic {
+            Arithmetic::U64 => simulate_with::<u64>(monkeys, rounds),
+            Arithmetic::U128 => simulate_with::<u128>(monkeys, rounds),
+            Arithmetic::Big => simulate_with::<BigUint>(monkeys, rounds),
+        },
+    )?;
+
+    Ok(InspectionReport::new(rounds, counts))
+}
+
+/// Like [`simulate`] with a fixed worry type, the operations must not divide.
+fn simulate_with<W: Worry>(monkeys: &[Monkey], rounds: u64) -> Result<Vec<u64>, Failure> {
+    let reduction = Reduction::Modulo(lcm(monkeys).ok_or(Failure::Overflow)?);
+    let mut counts = vec![0; monkeys.len()];
+
+    for (start, monkey) in monkeys.iter().enumerate() {
+        for &item in &monkey.items {
+            let item_counts = follow_item(monkeys, start, W::from(item), &reduction, rounds)?;
+
+            for (count, item_count) in counts.iter_mut().zip(item_counts) {
+                *count += item_count;
+            }
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Inspections per monkey of a single item starting at monkey `start` over `rounds` rounds.
+fn follow_item<W: Worry>(
+    monkeys: &[Monkey],
+    start: usize,
+    item: W,
+    reduction: &Reduction<W>,
+    rounds: u64,
+) -> Result<Vec<u64>, Failure> {
+    let n = monkeys.len();
+    // Round in which each state was first seen at the start of a round
+    let mut seen: HashMap<(usize, W), u64> = HashMap::new();
+    // Inspections per monkey before each round, `n` entries per round
+    let mut cumulative = vec![0; n];
+    let before = |cumulative: &[u64], round: u64| {
+        let offset = round as usize * n;
+        cumulative[offset..offset + n].to_vec()
     };
+    let mut state = (start, item);
+
+    for round in 0..rounds {
+        if let Some(&cycle_start) = seen.get(&state) {
+            let cycle_length = round - cycle_start;
+            let (cycles, remainder) = (
+                (rounds - round) / cycle_length,
+                (rounds - round) % cycle_length,
+            );
+            let (start, end, partial) = (
+                before(&cumulative, cycle_start),
+                before(&cumulative, round),
+                before(&cumulative, cycle_start + remainder),
+            );
+
+            return Ok((0..n)
+                .map(|i| end[i] + cycles * (end[i] - start[i]) + (partial[i] - start[i]))
+                .collect());
+        }
+        seen.insert(state.clone(), round);
+
+        let (mut monkey, mut worry) = state;
+        let mut counts = before(&cumulative, round);
+
+        // An item thrown to a later monkey is inspected again in the same round
+        loop {
+            worry = monkeys[monkey].inspect(&worry, reduction)?;
+            counts[monkey] += 1;
+
+            let target = monkeys[monkey].test.outcome(&worry);
+            let next_round = target <= monkey;
+            monkey = target;
+
+            if next_round {
+                break;
+            }
+        }
+
+        cumulative.extend(counts);
+        state = (monkey, worry);
+    }
+
+    Ok(before(&cumulative, rounds))
+}
+
+/// How many items each monkey inspected, in the smallest [`Arithmetic`] that doesn't overflow.
+///
+/// This simulates every round one by one, see [`simulate`] for a faster alternative without
+/// relief.
+fn inspections(monkeys: &[Monkey], rounds: u64, worried: bool) -> Result<Vec<u64>, Error> {
+    widening(
+        Arithmetic::select(monkeys, worried),
+        |arithmetic| match arithmetic {
+            Arithmetic::U64 => inspections_with::<u64>(monkeys, rounds, worried),
+            Arithmetic::U128 => inspections_with::<u128>(monkeys, rounds, worried),
+            Arithmetic::Big => inspections_with::<BigUint>(monkeys, rounds, worried),
+        },
+    )
+}
+
+/// Run `simulation` with `arithmetic`, retrying with wider types as long as it overflows.
+///
+/// Other failures can't be fixed by a wider type and are returned right away.
+fn widening<T, F>(mut arithmetic: Arithmetic, mut simulation: F) -> Result<T, Error>
+where
+    F: FnMut(Arithmetic) -> Result<T, Failure>,
+{
+    loop {
+        match simulation(arithmetic) {
+            Err(Failure::Overflow) => {
+                arithmetic = arithmetic
+                    .wider()
+                    .expect("Arbitrary precision can't overflow");
+            }
+            result => return result.map_err(Error::from),
+        }
+    }
+}
+
+/// Like [`inspections`] with a fixed worry type.
+fn inspections_with<W: Worry>(
+    monkeys: &[Monkey],
+    rounds: u64,
+    worried: bool,
+) -> Result<Vec<u64>, Failure> {
+    let mut counts = vec![0; monkeys.len()];
+
+    run::<W, _>(monkeys, rounds, worried, |event| {
+        counts[event.monkey] += 1;
+    })?;
+
+    Ok(counts)
+}
+
+/// Simulate `rounds` rounds one by one.
+fn run<W, F>(monkeys: &[Monkey], rounds: u64, worried: bool, mut callback: F) -> Result<(), Failure>
+where
+    W: Worry,
+    F: FnMut(Event<W>),
+{
+    let reduction = Reduction::select(monkeys, worried)?;
+    let mut items: Vec<Vec<W>> = monkeys
+        .iter()
+        .map(|monkey| monkey.items.iter().map(|&item| W::from(item)).collect())
+        .collect();
+
+    for round in 1..=rounds {
+        do_round(monkeys, &mut items, &reduction, round, &mut callback)?;
+    }
 
-    sorted.into_iter().take(2).product()
+    Ok(())
 }
 
-fn do_round<F>(monkeys: &mut [Monkey], worried: bool, mut callback: F)
+/// Simulate a single round.
+fn do_round<W, F>(
+    monkeys: &[Monkey],
+    items: &mut [Vec<W>],
+    reduction: &Reduction<W>,
+    round: u64,
+    mut callback: F,
+) -> Result<(), Failure>
 where
-    F: FnMut(usize),
+    W: Worry,
+    F: FnMut(Event<W>),
 {
-    let div_mod = monkeys
+    for (i, monkey) in monkeys.iter().enumerate() {
+        for item in mem::take(&mut items[i]) {
+            let new_value = monkey.inspect(&item, reduction)?;
+            let divisible = monkey.test.divisible(&new_value);
+            let new_monkey = monkey.test.target(divisible);
+
+            items[new_monkey].push(new_value.clone());
+
+            callback(Event {
+                round,
+                monkey: i,
+                before: item,
+                after: new_value,
+                divisible,
+                target: new_monkey,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// A single inspection of an item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event<W = u64> {
+    /// The round the inspection happened in, starting at 1.
+    pub round: u64,
+    /// The inspecting monkey.
+    pub monkey: usize,
+    /// Worry level before the inspection.
+    pub before: W,
+    /// Worry level after the operation and its [`Reduction`].
+    pub after: W,
+    /// Whether `after` passed the monkey's divisibility test.
+    pub divisible: bool,
+    /// The monkey the item was thrown to.
+    pub target: usize,
+}
+
+/// Every inspection of a simulation, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    pub events: Vec<Event>,
+}
+
+impl Trace {
+    /// Record `rounds` rounds, with the same rules as [`inspections`].
+    ///
+    /// Worry levels are recorded as [`u64`], so this fails if they don't fit.
+    pub fn record(monkeys: &[Monkey], rounds: u64, worried: bool) -> Result<Self, Error> {
+        let mut events = vec![];
+
+        run::<u64, _>(monkeys, rounds, worried, |event| events.push(event)).map_err(|failure| {
+            match failure {
+                Failure::Overflow => Error::from("Worry levels don't fit in 64 bits"),
+                failure => failure.into(),
+            }
+        })?;
+
+        Ok(Self { events })
+    }
+
+    /// One JSON object per event and line.
+    pub fn to_json_lines(&self) -> String {
+        self.events
+            .iter()
+            .map(|event| serde_json::to_string(event).expect("Events can always be serialized"))
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    pub fn from_json_lines(input: &str) -> Result<Self, Error> {
+        let events = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    Error::from(format!("Invalid event: {e}"))
+                        .offset(Location::new(idx + 1, 1), line)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { events })
+    }
+
+    /// Reconstruct the monkeys as they were after `round` rounds, starting from `monkeys`.
+    ///
+    /// Round 0 is the initial state. Fails if the trace doesn't match `monkeys`, e.g. if an event
+    /// inspects an item the monkey doesn't hold.
+    pub fn replay(&self, monkeys: &[Monkey], round: u64) -> Result<Vec<Monkey>, Error> {
+        let mut monkeys = monkeys.to_vec();
+
+        for (idx, event) in self.events.iter().enumerate() {
+            if event.round > round {
+                break;
+            }
+
+            let monkey = monkeys
+                .get_mut(event.monkey)
+                .ok_or_else(|| format!("Event {idx}: monkey {} doesn't exist", event.monkey))?;
+
+            if monkey.items.first() != Some(&event.before) {
+                return Err(format!(
+                    "Event {idx}: monkey {} doesn't hold an item with worry level {} next",
+                    event.monkey, event.before
+                )
+                .into());
+            }
+            monkey.items.remove(0);
+
+            monkeys
+                .get_mut(event.target)
+                .ok_or_else(|| format!("Event {idx}: monkey {} doesn't exist", event.target))?
+                .items
+                .push(event.after);
+        }
+
+        Ok(monkeys)
+    }
+}
+
+/// Least common multiple of all divisors, [`None`] if it overflows `W`.
+fn lcm<W: Worry>(monkeys: &[Monkey]) -> Option<W> {
+    fn gcd<W: Worry>(a: W, b: W) -> W {
+        if b.is_zero() {
+            a
+        } else {
+            let r = a % &b;
+            gcd(b, r)
+        }
+    }
+
+    monkeys.iter().try_fold(W::from(1), |lcm, monkey| {
+        let operand = W::from(monkey.test.operand);
+        let divisor = gcd(lcm.clone(), operand.clone());
+
+        lcm.checked_div(&divisor)?.checked_mul(&operand)
+    })
+}
+
+/// The most bits an exactly tracked worry level may take, see [`Reduction::Exact`].
+///
+/// Large enough for worry levels that grow by a constant factor every inspection to last for
+/// thousands of rounds, while ones that keep squaring run into it within a few.
+pub const MAX_EXACT_BITS: u64 = 1 << 16;
+
+/// Why a worry level couldn't be computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// It doesn't fit the worry type, a wider one might do.
+    Overflow,
+    Negative,
+    DivisionByZero,
+    /// It needs more than [`MAX_EXACT_BITS`] bits.
+    TooLarge,
+}
+
+impl From<Failure> for Error {
+    fn from(failure: Failure) -> Self {
+        match failure {
+            Failure::Overflow => "Worry level overflowed".into(),
+            Failure::Negative => "Worry level became negative".into(),
+            Failure::DivisionByZero => "Worry level was divided by zero".into(),
+            Failure::TooLarge => {
+                format!("Worry level grew past {MAX_EXACT_BITS} bits, too large to track exactly")
+                    .into()
+            }
+        }
+    }
+}
+
+/// How worry levels are kept in check after each inspection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reduction<W> {
+    /// Divided by 3, the relief of part one.
+    Relief,
+    /// Reduced modulo the least common multiple of the divisors, which every test survives.
+    Modulo(W),
+    /// Not at all, up to [`MAX_EXACT_BITS`] bits.
+    Exact,
+}
+
+impl<W: Worry> Reduction<W> {
+    /// Without relief worry levels only matter modulo the divisors, unless an operation divides.
+    /// Division doesn't survive the modular reduction, so those are simulated exactly.
+    ///
+    /// Fails if the least common multiple of the divisors overflows `W`.
+    fn select(monkeys: &[Monkey], worried: bool) -> Result<Self, Failure> {
+        if !worried {
+            Ok(Self::Relief)
+        } else if has_division(monkeys) {
+            Ok(Self::Exact)
+        } else {
+            lcm(monkeys).map(Self::Modulo).ok_or(Failure::Overflow)
+        }
+    }
+}
+
+/// Whether any monkey's operation divides.
+fn has_division(monkeys: &[Monkey]) -> bool {
+    monkeys
         .iter()
-        .fold(1, |acc, monkey| acc * monkey.test.operand);
+        .any(|monkey| monkey.operation.expression.has_division())
+}
 
-    for i in 0..monkeys.len() {
-        for j in 0..monkeys[i].items.len() {
-            let item = monkeys[i].items[j];
-            let new_value = monkeys[i].inspect(item, worried, div_mod);
-            let new_monkey = monkeys[i].test.outcome(new_value);
+/// An unsigned integer type worry levels can be tracked in.
+trait Worry:
+    Clone
+    + Ord
+    + Hash
+    + fmt::Display
+    + From<u64>
+    + Zero
+    + CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + CheckedDiv
+    + for<'a> Rem<&'a Self, Output = Self>
+{
+    /// The number of bits needed to represent the value.
+    fn bits(&self) -> u64;
+}
 
-            monkeys[new_monkey].items.push(new_value);
+impl Worry for u64 {
+    fn bits(&self) -> u64 {
+        u64::from(u64::BITS - self.leading_zeros())
+    }
+}
+
+impl Worry for u128 {
+    fn bits(&self) -> u64 {
+        u64::from(u128::BITS - self.leading_zeros())
+    }
+}
+
+impl Worry for BigUint {
+    fn bits(&self) -> u64 {
+        BigUint::bits(self)
+    }
+}
+
+/// The integer type used for worry levels during a simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    U64,
+    U128,
+    /// Arbitrary precision, can't overflow.
+    Big,
+}
 
-            // Monkey inspected an item
-            callback(i);
+impl Arithmetic {
+    /// The smallest type that is guaranteed to fit every intermediate value.
+    ///
+    /// When `worried` worry levels are kept below the least common multiple of the divisors,
+    /// which bounds every intermediate value. Otherwise, or when an operation divides and
+    /// worry levels aren't reduced at all, there's no such bound. Then the simulation starts out
+    /// with [`Arithmetic::U64`] and is retried with a wider type if it overflows.
+    pub fn select(monkeys: &[Monkey], worried: bool) -> Self {
+        if !worried || has_division(monkeys) {
+            return Self::U64;
         }
 
-        monkeys[i].items.clear();
+        let modulus: BigUint = lcm(monkeys).expect("Arbitrary precision can't overflow");
+        let largest_item = monkeys
+            .iter()
+            .flat_map(|monkey| monkey.items.iter().copied())
+            .max()
+            .unwrap_or_default();
+        let old = BigUint::from(largest_item).max(&modulus - 1u32);
+        let largest = monkeys
+            .iter()
+            .map(|monkey| monkey.operation.bound(&old, &modulus))
+            .fold(modulus.clone(), BigUint::max);
+
+        if largest <= BigUint::from(u64::MAX) {
+            Self::U64
+        } else if largest <= BigUint::from(u128::MAX) {
+            Self::U128
+        } else {
+            Self::Big
+        }
+    }
+
+    fn wider(self) -> Option<Self> {
+        match self {
+            Self::U64 => Some(Self::U128),
+            Self::U128 => Some(Self::Big),
+            Self::Big => None,
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Monkey {
     id: usize,
     items: Vec<u64>,
@@ -69,36 +597,176 @@ impl Monkey {
         }
     }
 
-    fn inspect(&self, item: u64, worried: bool, div_mod: u64) -> u64 {
-        if worried {
-            self.operation.apply(item, div_mod)
-        } else {
-            self.operation.apply(item, div_mod) / 3
+    pub fn items(&self) -> &[u64] {
+        &self.items
+    }
+
+    fn inspect<W: Worry>(&self, item: &W, reduction: &Reduction<W>) -> Result<W, Failure> {
+        match reduction {
+            Reduction::Relief => Ok(self
+                .operation
+                .apply(item, None)?
+                .checked_div(&W::from(3))
+                .expect("3 isn't zero")),
+            Reduction::Modulo(m) => self.operation.apply(item, Some(m)),
+            Reduction::Exact => {
+                let worry = self.operation.apply(item, None)?;
+
+                if worry.bits() > MAX_EXACT_BITS {
+                    Err(Failure::TooLarge)
+                } else {
+                    Ok(worry)
+                }
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Operation {
-    MulOld(u64),
-    AddOld(u64),
-    MulOldSelf,
-    AddOldSelf,
+/// How a monkey changes the worry level of an item, i.e. the right hand side of `new = ...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    expression: Expression,
 }
 
 impl Operation {
-    fn apply(self, item: u64, div_mod: u64) -> u64 {
-        use Operation::*;
+    pub fn new(expression: Expression) -> Self {
+        Self { expression }
+    }
+
+    /// Apply the operation to an item, evaluated entirely modulo `modulus` if given.
+    ///
+    /// Division doesn't survive the modular reduction, so the operation must not divide when
+    /// `modulus` is given, see [`Reduction`]. Without `modulus` the result is exact and only
+    /// limited by `W`, [`Monkey::inspect`] caps it at [`MAX_EXACT_BITS`] bits when worry levels
+    /// aren't reduced at all.
+    fn apply<W: Worry>(&self, item: &W, modulus: Option<&W>) -> Result<W, Failure> {
+        match modulus {
+            Some(m) => self.expression.evaluate_mod(item, m),
+            None => self.expression.evaluate(item),
+        }
+    }
+
+    /// The largest intermediate value [`Operation::apply`] can produce for items up to `old`
+    /// when reducing modulo `modulus`. The operation must not divide.
+    fn bound(&self, old: &BigUint, modulus: &BigUint) -> BigUint {
+        let mut largest = BigUint::zero();
+        self.expression.bound(old, Some(modulus), &mut largest);
+
+        largest
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    /// The current worry level.
+    Old,
+    Constant(u64),
+    Binary(Box<Expression>, BinaryOp, Box<Expression>),
+}
+
+impl Expression {
+    pub fn binary(lhs: Expression, op: BinaryOp, rhs: Expression) -> Self {
+        Self::Binary(Box::new(lhs), op, Box::new(rhs))
+    }
+
+    fn has_division(&self) -> bool {
+        match self {
+            Expression::Old | Expression::Constant(_) => false,
+            Expression::Binary(lhs, op, rhs) => {
+                *op == BinaryOp::Div || lhs.has_division() || rhs.has_division()
+            }
+        }
+    }
+
+    /// Evaluate exactly.
+    fn evaluate<W: Worry>(&self, old: &W) -> Result<W, Failure> {
         match self {
-            MulOld(arg) => (item * arg) % div_mod,
-            AddOld(arg) => (item + arg) % div_mod,
-            MulOldSelf => (item * item) % div_mod,
-            AddOldSelf => (item + item) % div_mod,
+            Expression::Old => Ok(old.clone()),
+            Expression::Constant(c) => Ok(W::from(*c)),
+            Expression::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(old)?, rhs.evaluate(old)?);
+
+                match op {
+                    BinaryOp::Add => lhs.checked_add(&rhs).ok_or(Failure::Overflow),
+                    BinaryOp::Sub => lhs.checked_sub(&rhs).ok_or(Failure::Negative),
+                    BinaryOp::Mul => lhs.checked_mul(&rhs).ok_or(Failure::Overflow),
+                    BinaryOp::Div => lhs.checked_div(&rhs).ok_or(Failure::DivisionByZero),
+                }
+            }
+        }
+    }
+
+    /// Evaluate modulo `m`, which can only overflow. The expression must not contain division.
+    fn evaluate_mod<W: Worry>(&self, old: &W, m: &W) -> Result<W, Failure> {
+        match self {
+            Expression::Old => Ok(old.clone() % m),
+            Expression::Constant(c) => Ok(W::from(*c) % m),
+            Expression::Binary(lhs, op, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate_mod(old, m)?, rhs.evaluate_mod(old, m)?);
+                let result = match op {
+                    BinaryOp::Add => lhs.checked_add(&rhs),
+                    BinaryOp::Sub => m.checked_sub(&rhs).and_then(|r| lhs.checked_add(&r)),
+                    BinaryOp::Mul => lhs.checked_mul(&rhs),
+                    BinaryOp::Div => unreachable!("Division can't be evaluated modulo {}", m),
+                };
+
+                Ok(result.ok_or(Failure::Overflow)? % m)
+            }
+        }
+    }
+
+    /// The largest value this expression evaluates to for `old` up to the given value, mirroring
+    /// [`Expression::evaluate`], or [`Expression::evaluate_mod`] if `modulus` is given.
+    ///
+    /// `largest` is raised to the largest intermediate value along the way.
+    fn bound(&self, old: &BigUint, modulus: Option<&BigUint>, largest: &mut BigUint) -> BigUint {
+        let value = match self {
+            Expression::Old => old.clone(),
+            Expression::Constant(c) => BigUint::from(*c),
+            Expression::Binary(lhs, op, rhs) => {
+                let lhs = lhs.bound(old, modulus, largest);
+                let rhs = rhs.bound(old, modulus, largest);
+
+                match (op, modulus) {
+                    (BinaryOp::Add, _) => lhs + rhs,
+                    // Evaluated as `lhs + (m - rhs)`
+                    (BinaryOp::Sub, Some(m)) => lhs + m,
+                    (BinaryOp::Sub, None) => lhs,
+                    (BinaryOp::Mul, _) => lhs * rhs,
+                    (BinaryOp::Div, _) => lhs,
+                }
+            }
+        };
+
+        if value > *largest {
+            *largest = value.clone();
+        }
+
+        match modulus {
+            Some(m) => value.min(m - 1u32),
+            None => value,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Test {
     operand: u64,
     true_target: usize,
@@ -114,8 +782,16 @@ impl Test {
         }
     }
 
-    fn outcome(&self, item: u64) -> usize {
-        if item % self.operand == 0 {
+    fn outcome<W: Worry>(&self, item: &W) -> usize {
+        self.target(self.divisible(item))
+    }
+
+    fn divisible<W: Worry>(&self, item: &W) -> bool {
+        (item.clone() % &W::from(self.operand)).is_zero()
+    }
+
+    fn target(&self, divisible: bool) -> usize {
+        if divisible {
             self.true_target
         } else {
             self.false_target
@@ -123,61 +799,267 @@ impl Test {
     }
 }
 
+impl FromStr for Monkey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+        let mut field = |prefix: &str| {
+            let line = lines
+                .next()
+                .ok_or_else(|| Error::parse(s, &s[s.len()..], format!("Missing `{prefix}`")))?;
+
+            line.strip_prefix(prefix)
+                .map(str::trim)
+                .ok_or_else(|| Error::parse(s, line, format!("Expected `{prefix}`")))
+        };
+
+        let header = field("Monkey")?;
+        let id = header
+            .strip_suffix(':')
+            .ok_or_else(|| Error::parse(s, header, "Expected monkey header to end with `:`"))?;
+        let id = parse_number(s, id)?;
+
+        let items = field("Starting items:")?
+            .split(',')
+            .map(str::trim)
+            .filter(|i| !i.is_empty())
+            .map(|i| parse_number(s, i))
+            .collect::<Result<_, _>>()?;
+
+        let operation = field("Operation:")?;
+        let expression = operation
+            .strip_prefix("new =")
+            .map(str::trim)
+            .ok_or_else(|| Error::parse(s, operation, "Expected `new =`"))?;
+        let operation = expression
+            .parse()
+            .map_err(|e: Error| e.offset(Location::of(s, expression), expression))?;
+
+        let operand = parse_number(s, field("Test: divisible by")?)?;
+        let true_target = parse_number(s, field("If true: throw to monkey")?)?;
+        let false_target = parse_number(s, field("If false: throw to monkey")?)?;
+
+        if let Some(extra) = lines.next() {
+            return Err(Error::parse(s, extra, "Unexpected line"));
+        }
+
+        Ok(Self::new(
+            id,
+            items,
+            operation,
+            Test::new(operand, true_target, false_target),
+        ))
+    }
+}
+
+impl FromStr for Operation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let expression = parser.expression(0)?;
+
+        match parser.peek() {
+            Some(token) if token.kind == TokenKind::Unknown => Err(Error::parse(
+                s,
+                token.text,
+                format!("Unknown operator `{}`", token.text),
+            )),
+            Some(token) => Err(Error::parse(s, token.text, "Unexpected trailing input")),
+            None => Ok(Self::new(expression)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Old,
+    Number,
+    Op(BinaryOp),
+    Open,
+    Close,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+}
+
+/// Precedence climbing parser for operation expressions.
+struct Parser<'a> {
+    input: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, rest: input }
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        let rest = self.rest.trim_start();
+        let c = rest.chars().next()?;
+        let (kind, len) = match c {
+            '0'..='9' => (
+                TokenKind::Number,
+                rest.find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len()),
+            ),
+            _ if rest.starts_with("old") => (TokenKind::Old, 3),
+            '+' => (TokenKind::Op(BinaryOp::Add), 1),
+            '-' => (TokenKind::Op(BinaryOp::Sub), 1),
+            '*' => (TokenKind::Op(BinaryOp::Mul), 1),
+            '/' => (TokenKind::Op(BinaryOp::Div), 1),
+            '(' => (TokenKind::Open, 1),
+            ')' => (TokenKind::Close, 1),
+            // Unknown tokens extend to the next whitespace
+            _ => (
+                TokenKind::Unknown,
+                rest.find(char::is_whitespace).unwrap_or(rest.len()),
+            ),
+        };
+
+        Some(Token {
+            kind,
+            text: &rest[..len],
+        })
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, Error> {
+        let token = self.peek().ok_or_else(|| {
+            Error::parse(
+                self.input,
+                &self.input[self.input.len()..],
+                "Unexpected end of operation",
+            )
+        })?;
+        let rest = self.rest.trim_start();
+        self.rest = &rest[token.text.len()..];
+
+        Ok(token)
+    }
+
+    fn expression(&mut self, min_precedence: u8) -> Result<Expression, Error> {
+        let mut lhs = self.primary()?;
+
+        while let Some(Token {
+            kind: TokenKind::Op(op),
+            ..
+        }) = self.peek()
+        {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.next()?;
+
+            let start = self.rest.trim_start();
+            let rhs = self.expression(op.precedence() + 1)?;
+            if op == BinaryOp::Div && rhs == Expression::Constant(0) {
+                let text = start[..start.len() - self.rest.len()].trim_end();
+
+                return Err(Error::parse(self.input, text, "Division by zero"));
+            }
+            lhs = Expression::binary(lhs, op, rhs);
+        }
+
+        Ok(lhs)
+    }
+
+    fn primary(&mut self) -> Result<Expression, Error> {
+        let token = self.next()?;
+
+        match token.kind {
+            TokenKind::Old => Ok(Expression::Old),
+            TokenKind::Number => parse_number(self.input, token.text).map(Expression::Constant),
+            TokenKind::Open => {
+                let expression = self.expression(0)?;
+                let close = self.next()?;
+
+                if close.kind != TokenKind::Close {
+                    return Err(Error::parse(self.input, close.text, "Expected `)`"));
+                }
+
+                Ok(expression)
+            }
+            TokenKind::Op(_) | TokenKind::Close | TokenKind::Unknown => Err(Error::parse(
+                self.input,
+                token.text,
+                "Expected `old`, a number or `(`",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Old => write!(f, "old"),
+            Expression::Constant(c) => write!(f, "{c}"),
+            Expression::Binary(lhs, op, rhs) => {
+                let wrap = |e: &Expression, f: &mut fmt::Formatter<'_>, min: u8| match e {
+                    Expression::Binary(_, inner, _) if inner.precedence() < min => {
+                        write!(f, "({e})")
+                    }
+                    _ => write!(f, "{e}"),
+                };
+
+                wrap(lhs, f, op.precedence())?;
+                write!(f, " {op} ")?;
+                // Operators are left associative, so a right hand side with the same precedence
+                // needs parentheses too
+                wrap(rhs, f, op.precedence() + 1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOp::Add => write!(f, "+"),
+            BinaryOp::Sub => write!(f, "-"),
+            BinaryOp::Mul => write!(f, "*"),
+            BinaryOp::Div => write!(f, "/"),
+        }
+    }
+}
+
+fn parse_number<T>(s: &str, part: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    part.parse()
+        .map_err(|e| Error::parse(s, part, format!("Invalid number: {e}")))
+}
+
 pub mod prelude {
-    pub use super::{star_one, star_two, Monkey, Operation, Test};
+    pub use super::{star_one, star_two, BinaryOp, Expression, Monkey, Operation, Test};
 }
 
 macro_rules! monkey {
     ({
         Monkey $n:literal:
         Starting items: $($items:literal),+
-        Operation: new = old * $op_arg:literal
-        Test: divisible by $div_arg:literal
-            If true: throw to monkey $true_target:literal
-            If false: throw to monkey $false_target:literal
-    }) => {
-        Monkey::new(
-            $n,
-            vec![$($items),+],
-            Operation::MulOld($op_arg),
-            Test::new(
-                     $div_arg,
-            $true_target,
-            $false_target
-            )
-        )
-    };
-    ({
-        Monkey $n:literal:
-        Starting items: $($items: literal),+
-        Operation: new = old * old
-        Test: divisible by $div_arg:literal
-            If true: throw to monkey $true_target:literal
-            If false: throw to monkey $false_target:literal
+        Operation: new = $($rest:tt)+
     }) => {
-        Monkey::new(
-            $n,
-            vec![$($items),+],
-            Operation::MulOldSelf,
-            Test::new(
-                     $div_arg,
-            $true_target,
-            $false_target
-            )
-        )
+        monkey!(@operation $n, [$($items),+], [] $($rest)+)
     };
-    ({
-        Monkey $n:literal:
-        Starting items: $($items: literal),+
-        Operation: new = old + $op_arg: literal
+    // Munch operation tokens until the test is reached
+    (@operation $n:literal, [$($items:literal),+], [$($op:tt)*]
         Test: divisible by $div_arg:literal
             If true: throw to monkey $true_target:literal
             If false: throw to monkey $false_target:literal
-    }) => {
+    ) => {
         Monkey::new(
             $n,
             vec![$($items),+],
-            Operation::AddOld($op_arg),
+            stringify!($($op)*)
+                .parse::<Operation>()
+                .expect("Invalid monkey operation"),
             Test::new(
                      $div_arg,
             $true_target,
@@ -185,24 +1067,8 @@ macro_rules! monkey {
             )
         )
     };
-    ({
-        Monkey $n:literal:
-        Starting items: $($items: literal),+
-        Operation: new = old + old
-        Test: divisible by $div_arg:literal
-            If true: throw to monkey $true_target:literal
-            If false: throw to monkey $false_target:literal
-    }) => {
-        Monkey::new(
-            $n,
-            vec![$($items),+],
-            Operation::AddOldSelf,
-            Test::new(
-                     $div_arg,
-            $true_target,
-            $false_target
-            )
-        )
+    (@operation $n:literal, [$($items:literal),+], [$($op:tt)*] $next:tt $($rest:tt)*) => {
+        monkey!(@operation $n, [$($items),+], [$($op)* $next] $($rest)*)
     };
 }
 
@@ -220,7 +1086,45 @@ macro_rules! monkeys {
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two, Monkey, Operation, Test};
+    use std::mem;
+
+    use num_bigint::BigUint;
+
+    use super::{
+        inspections, inspections_with, lcm, simulate, star_one, star_two, Arithmetic, Day11,
+        Failure, InspectionReport, Monkey, Operation, Test, Trace,
+    };
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = r#"
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+"#;
 
     fn test_monkeys() -> Vec<Monkey> {
         monkeys!(
@@ -262,13 +1166,451 @@ mod tests {
         )
     }
 
+    /// Monkeys that test for divisibility by large primes, squaring items every round.
+    fn large_prime_monkeys([a, b, c, d]: [u64; 4]) -> Vec<Monkey> {
+        let input = format!(
+            "
+Monkey 0:
+  Starting items: 79, 98, {a}
+  Operation: new = old * 19
+  Test: divisible by {a}
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, {b}
+  Operation: new = old + {b}
+  Test: divisible by {b}
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by {c}
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old - 3
+  Test: divisible by {d}
+    If true: throw to monkey 0
+    If false: throw to monkey 2
+"
+        );
+
+        Day11::parse(&input).unwrap()
+    }
+
+    /// Reference simulation that never reduces worry levels.
+    fn exact_inspections(monkeys: &[Monkey], rounds: usize) -> Vec<u64> {
+        let mut items: Vec<Vec<BigUint>> = monkeys
+            .iter()
+            .map(|monkey| monkey.items.iter().map(|&item| item.into()).collect())
+            .collect();
+        let mut counts = vec![0; monkeys.len()];
+
+        for _ in 0..rounds {
+            for (i, monkey) in monkeys.iter().enumerate() {
+                for item in mem::take(&mut items[i]) {
+                    let new_value = monkey.operation.expression.evaluate(&item).unwrap();
+
+                    items[monkey.test.outcome(&new_value)].push(new_value);
+                    counts[i] += 1;
+                }
+            }
+        }
+
+        counts
+    }
+
+    #[test]
+    fn test_select_arithmetic() {
+        assert_eq!(Arithmetic::select(&test_monkeys(), true), Arithmetic::U64);
+        assert_eq!(Arithmetic::select(&test_monkeys(), false), Arithmetic::U64);
+    }
+
+    #[test]
+    fn test_large_primes_u128() {
+        // The LCM fits in 64 bits, but squaring items below it doesn't
+        let monkeys = large_prime_monkeys([1048583, 1048681, 1048583, 1048681]);
+
+        assert_eq!(Arithmetic::select(&monkeys, true), Arithmetic::U128);
+        assert_eq!(
+            inspections_with::<u64>(&monkeys, 12, true),
+            Err(Failure::Overflow)
+        );
+        assert_eq!(
+            inspections(&monkeys, 12, true).unwrap(),
+            exact_inspections(&monkeys, 12)
+        );
+    }
+
+    #[test]
+    fn test_large_primes_big() {
+        // The LCM doesn't even fit in 64 bits
+        let monkeys = large_prime_monkeys([8589934609, 8589935597, 8589939601, 1048583]);
+
+        assert_eq!(lcm::<u64>(&monkeys), None);
+        assert_eq!(Arithmetic::select(&monkeys, true), Arithmetic::Big);
+        assert_eq!(
+            inspections_with::<u128>(&monkeys, 12, true),
+            Err(Failure::Overflow)
+        );
+        assert_eq!(
+            inspections(&monkeys, 12, true).unwrap(),
+            exact_inspections(&monkeys, 12)
+        );
+    }
+
+    #[test]
+    fn test_relief_overflow() {
+        // Without reduction the worry levels outgrow 64 bits within a few rounds
+        let monkeys = large_prime_monkeys([1048583, 1048681, 1048583, 1048681]);
+
+        assert_eq!(
+            inspections_with::<u64>(&monkeys, 8, false),
+            Err(Failure::Overflow)
+        );
+        assert_eq!(
+            inspections(&monkeys, 8, false).unwrap(),
+            inspections_with::<BigUint>(&monkeys, 8, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_simulate() {
+        let monkeys = test_monkeys();
+
+        assert_eq!(simulate(&monkeys, 20).unwrap().counts, vec![99, 97, 8, 103]);
+        assert_eq!(
+            simulate(&monkeys, 10_000).unwrap().counts,
+            vec![52166, 47830, 1938, 52013]
+        );
+
+        for rounds in [0, 1, 2, 1000] {
+            assert_eq!(
+                simulate(&monkeys, rounds).unwrap().counts,
+                inspections(&monkeys, rounds, true).unwrap(),
+                "After {rounds} rounds"
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate_cycles() {
+        // Small divisors make for short cycles, so most rounds are extrapolated
+        let monkeys = Day11::parse(
+            &INPUT
+                .replace("divisible by 23", "divisible by 2")
+                .replace("divisible by 19", "divisible by 3")
+                .replace("divisible by 13", "divisible by 5")
+                .replace("divisible by 17", "divisible by 7"),
+        )
+        .unwrap();
+
+        for rounds in [100, 12_345, 100_000] {
+            assert_eq!(
+                simulate(&monkeys, rounds).unwrap().counts,
+                inspections(&monkeys, rounds, true).unwrap(),
+                "After {rounds} rounds"
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate_division() {
+        // Cycles of reduced worry levels don't describe dividing operations
+        let monkeys = dividing_monkeys();
+
+        for rounds in [5, 10, 20, 60] {
+            assert_eq!(
+                simulate(&monkeys, rounds).unwrap().counts,
+                exact_inspections(&monkeys, rounds as usize),
+                "After {rounds} rounds"
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate_unbounded_division() {
+        let monkeys = dividing_monkeys();
+        let mut squaring = monkeys.clone();
+        squaring[2].operation = "old * old / 2".parse().unwrap();
+
+        assert_eq!(simulate(&squaring, 10_000), Err(Failure::TooLarge.into()));
+        // Growing by a constant factor stays well within the limit for a while
+        assert!(simulate(&monkeys, 200).is_ok());
+    }
+
+    #[test]
+    fn test_simulate_partial_cycles() {
+        // The items cycle every 171 or 448 rounds, neither of which divides this, so all of
+        // them end part way through a cycle
+        let monkeys = test_monkeys();
+        let rounds = 1_000_003;
+        let report = simulate(&monkeys, rounds).unwrap();
+
+        assert_eq!(report.rounds, rounds);
+        assert_eq!(report.counts, inspections(&monkeys, rounds, true).unwrap());
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        let report = InspectionReport::new(20, vec![101, 95, 7, 105]);
+
+        assert_eq!(report.monkey_business(), 10605);
+    }
+
+    #[test]
+    fn test_trace() {
+        let monkeys = test_monkeys();
+        let trace = Trace::record(&monkeys, 20, false).unwrap();
+
+        let first = &trace.events[0];
+        assert_eq!(first.round, 1);
+        assert_eq!((first.monkey, first.before, first.after), (0, 79, 500));
+        assert_eq!((first.divisible, first.target), (false, 3));
+
+        let counts = trace.events.iter().fold(vec![0; 4], |mut counts, event| {
+            counts[event.monkey] += 1;
+            counts
+        });
+        assert_eq!(counts, vec![101, 95, 7, 105]);
+
+        let json = trace.to_json_lines();
+        assert_eq!(json.lines().count(), trace.events.len());
+        assert_eq!(Trace::from_json_lines(&json), Ok(trace));
+    }
+
+    #[test]
+    fn test_trace_invalid_json() {
+        let input = "{\"round\":1,\"monkey\":0,\"before\":79,\"after\":500,\"divisible\":false,\"target\":3}\n{}";
+
+        let Err(Error::Parse(e)) = Trace::from_json_lines(input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 1));
+    }
+
+    #[test]
+    fn test_replay() {
+        let monkeys = test_monkeys();
+        let trace = Trace::record(&monkeys, 20, false).unwrap();
+        let items = |round| -> Vec<Vec<u64>> {
+            trace
+                .replay(&monkeys, round)
+                .unwrap()
+                .iter()
+                .map(|monkey| monkey.items().to_vec())
+                .collect()
+        };
+
+        assert_eq!(
+            items(0),
+            vec![
+                vec![79, 98],
+                vec![54, 65, 75, 74],
+                vec![79, 60, 97],
+                vec![74]
+            ]
+        );
+        assert_eq!(
+            items(1),
+            vec![
+                vec![20, 23, 27, 26],
+                vec![2080, 25, 167, 207, 401, 1046],
+                vec![],
+                vec![]
+            ]
+        );
+        assert_eq!(
+            items(20),
+            vec![
+                vec![10, 12, 14, 26, 34],
+                vec![245, 93, 53, 199, 115],
+                vec![],
+                vec![]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_divergence() {
+        let monkeys = test_monkeys();
+        let mut trace = Trace::record(&monkeys, 2, false).unwrap();
+        trace.events[3].before += 1;
+
+        assert!(trace.replay(&monkeys, 1).is_err());
+        assert!(trace.replay(&monkeys, 0).is_ok());
+    }
+
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(test_monkeys()), 10605)
+        assert_eq!(star_one(INPUT), 10605)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(test_monkeys()), 2713310158);
+        assert_eq!(star_two(INPUT), 2713310158);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Day11::parse(INPUT), Ok(test_monkeys()));
+    }
+
+    #[test]
+    fn test_parse_unknown_operation() {
+        let input = INPUT.replace("new = old + 6", "new = old % 6");
+
+        let Err(Error::Parse(e)) = Day11::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(11, 24));
+        assert_eq!(e.text, "%");
+    }
+
+    #[test]
+    fn test_parse_invalid_target() {
+        let input = INPUT.replace("throw to monkey 0", "throw to monkey 4");
+
+        assert!(matches!(Day11::parse(&input), Err(Error::Invalid(_))));
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let cases = [
+            ("old * 19", "old * 19"),
+            ("old+old", "old + old"),
+            ("(old + 2) * 3", "(old + 2) * 3"),
+            ("old - (3 - 1)", "old - (3 - 1)"),
+            ("old * old / 2 + 1", "old * old / 2 + 1"),
+        ];
+
+        for (input, expected) in cases {
+            let operation: Operation = input.parse().unwrap();
+
+            assert_eq!(operation.expression.to_string(), expected, "For {input}");
+        }
+
+        for invalid in ["", "old *", "(old + 1", "old 3", "* 3"] {
+            assert!(invalid.parse::<Operation>().is_err(), "For {invalid}");
+        }
+    }
+
+    #[test]
+    fn test_parse_division_by_zero() {
+        let input = INPUT.replace("new = old + 6", "new = old / (0)");
+
+        let Err(Error::Parse(e)) = Day11::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(11, 26));
+        assert_eq!(e.text, "(0)");
+    }
+
+    #[test]
+    fn test_invalid_worry_level() {
+        // Worry levels go negative, which no wider type can fix
+        let input = INPUT.replace("new = old + 6", "new = old - 100");
+        let monkeys = Day11::parse(&input).unwrap();
+
+        assert_eq!(
+            inspections_with::<u64>(&monkeys, 20, false),
+            Err(Failure::Negative)
+        );
+        assert!(inspections(&monkeys, 20, false).is_err());
+        assert!(Trace::record(&monkeys, 20, false).is_err());
+
+        // Dividing by zero only once it's evaluated
+        let input = INPUT.replace("new = old + 6", "new = old / (old - old)");
+        let monkeys = Day11::parse(&input).unwrap();
+
+        assert_eq!(
+            inspections_with::<u64>(&monkeys, 20, false),
+            Err(Failure::DivisionByZero)
+        );
+        assert!(inspections(&monkeys, 20, true).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let apply = |s: &str, item: u64, modulus: Option<u64>| {
+            s.parse::<Operation>()
+                .unwrap()
+                .apply(&item, modulus.as_ref())
+        };
+
+        assert_eq!(apply("old * 19", 79, Some(1000)), Ok(501));
+        assert_eq!(apply("(old + 2) * 3", 5, Some(1000)), Ok(21));
+        assert_eq!(apply("old - 7", 5, Some(10)), Ok(8));
+        assert_eq!(apply("old * old - old", 12, Some(1000)), Ok(132));
+        assert_eq!(apply("(old + 6) / 4", 10, None), Ok(4));
+        assert_eq!(apply("old * 2 / 3", 10, None), Ok(6));
+        assert_eq!(apply("old - 7", 5, None), Err(Failure::Negative));
+    }
+
+    /// Monkeys whose operations divide, passing items around between all of them.
+    fn dividing_monkeys() -> Vec<Monkey> {
+        let input = "
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 5 / 2
+  Test: divisible by 3
+    If true: throw to monkey 1
+    If false: throw to monkey 2
+
+Monkey 1:
+  Starting items: 54, 65, 75
+  Operation: new = old * 3 / 2
+  Test: divisible by 5
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 74
+  Operation: new = old + 7
+  Test: divisible by 7
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+        Day11::parse(input).unwrap()
+    }
+
+    #[test]
+    fn test_division_without_relief() {
+        // Dividing a reduced worry level gives different throws than dividing the exact one
+        let monkeys = dividing_monkeys();
+
+        assert_eq!(Arithmetic::select(&monkeys, true), Arithmetic::U64);
+        for rounds in [5, 10, 20, 60] {
+            assert_eq!(
+                inspections(&monkeys, rounds, true).unwrap(),
+                exact_inspections(&monkeys, rounds as usize),
+                "After {rounds} rounds"
+            );
+        }
+    }
+
+    #[test]
+    fn test_macro_expression() {
+        let monkeys = monkeys!(
+            {
+            Monkey 0:
+              Starting items: 79, 98
+              Operation: new = (old + 1) * old - 2
+              Test: divisible by 23
+                If true: throw to monkey 0
+                If false: throw to monkey 0
+            }
+        );
+
+        assert_eq!(
+            monkeys[0].operation.expression.to_string(),
+            "(old + 1) * old - 2"
+        );
     }
 }
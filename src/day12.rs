@@ -1,235 +1,238 @@
-use std::{
-    collections::{BinaryHeap, HashMap},
-    ops::Index,
-    str::FromStr,
-};
+use std::{ops::Index, str::FromStr};
+
+use crate::{grid::Grid, math::Vector2, search, Error, Solution};
 
 pub fn star_one(input: &str) -> usize {
-    let map: HeightMap = input.parse().expect("Failed to parse height map");
+    let map = Day12::parse(input).expect("Failed to parse height map");
 
-    map.shortest_path(map.start, map.end)
-        .expect("No shortest path found")
-        .len()
-        - 1
+    Day12::part_one(&map)
 }
 
 pub fn star_two(input: &str) -> usize {
-    let map: HeightMap = input.parse().expect("Failed to parse height map");
-    let came_from = map.djikstra(map.end);
-
-    let map = &map;
-    let came_from = &came_from;
-    let (loc, path) = (0..map.width())
-        .flat_map(|x| {
-            (0..map.height()).filter_map(move |y| {
-                let loc = (x, y);
-                if map[loc] != 0 {
-                    return None;
-                }
-
-                let path = reconstruct_path(came_from, loc);
-
-                (path[0] == map.end).then_some((loc, path))
-            })
-        })
-        .min_by_key(|(_, path)| path.len())
-        .expect("There should be at least one point at the lowest level");
+    let map = Day12::parse(input).expect("Failed to parse height map");
 
-    path.len() - 1
+    Day12::part_two(&map)
 }
 
-#[derive(Debug)]
-struct HeightMap {
-    map: Vec<Vec<u8>>,
-    start: (usize, usize),
-    end: (usize, usize),
-}
+pub struct Day12;
 
-impl HeightMap {
-    fn shortest_path(
-        &self,
-        from: (usize, usize),
-        to: (usize, usize),
-    ) -> Option<Vec<(usize, usize)>> {
-        let came_from = self.djikstra(to);
+impl Solution for Day12 {
+    type Input = HeightMap;
+    type PartOne = usize;
+    type PartTwo = usize;
 
-        Some(reconstruct_path(&came_from, from))
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
     }
 
-    fn valid_neighbors_backwards(&self, from: (usize, usize)) -> Vec<(usize, usize)> {
-        let current_height = self[from] as i16;
-        let (x, y) = from;
-        let mut result = Vec::with_capacity(4);
-
-        if x > 0 && current_height - (self[(x - 1, y)] as i16) <= 1 {
-            result.push((x - 1, y));
-        }
-
-        if y > 0 && current_height - (self[(x, y - 1)] as i16) <= 1 {
-            result.push((x, y - 1));
-        }
-
-        if x < self.width() - 1 && current_height - (self[(x + 1, y)] as i16) <= 1 {
-            result.push((x + 1, y));
-        }
-
-        if y < self.height() - 1 && current_height - (self[(x, y + 1)] as i16) <= 1 {
-            result.push((x, y + 1));
-        }
-
-        result
+    fn part_one(map: &Self::Input) -> Self::PartOne {
+        map.shortest_path(&Rules::default(), [map.start])
+            .expect("No shortest path found")
+            .len()
+            - 1
     }
 
-    fn width(&self) -> usize {
-        self.map[0].len()
-    }
+    fn part_two(map: &Self::Input) -> Self::PartTwo {
+        let field = map.distance_field(&Rules::default());
 
-    fn height(&self) -> usize {
-        self.map.len()
+        map.map
+            .iter()
+            .filter(|&(_, &height)| height == 0)
+            .filter_map(|(position, _)| field.distance_from(position))
+            .min()
+            .expect("There should be a path from at least one point at the lowest level")
     }
+}
 
-    fn djikstra(&self, from: (usize, usize)) -> HashMap<(usize, usize), (usize, usize)> {
-        let max = (self.width(), self.height());
-
-        let mut open = BinaryHeap::new();
-        for x in 0..max.0 {
-            for y in 0..max.1 {
-                let p = (x, y);
+/// How the map may be walked.
+///
+/// The [`Default`] is the puzzle's rules, climbing at most one level per step, descending any
+/// amount, and only moving orthogonally.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// The most a single step may climb.
+    pub max_ascent: u8,
+    /// The most a single step may descend, [`None`] for any amount.
+    pub max_descent: Option<u8>,
+    /// Whether diagonal steps are allowed.
+    pub diagonals: bool,
+    /// What each level climbed or descended costs, on top of one for every step.
+    pub height_cost: usize,
+}
 
-                if p != from {
-                    open.push(HeapEntry::new((x, y), usize::MAX));
-                }
-            }
+impl Rules {
+    /// Whether a step from `from` to `to` height is allowed.
+    pub fn allows(&self, from: u8, to: u8) -> bool {
+        if to >= from {
+            to - from <= self.max_ascent
+        } else {
+            self.max_descent.is_none_or(|max| from - to <= max)
         }
-        open.push(HeapEntry::new(from, 0));
-
-        let mut came_from: HashMap<(usize, usize), (usize, usize)> = Default::default();
-
-        let mut distance: HashMap<(usize, usize), usize> = Default::default();
-        distance.insert(from, 0);
-
-        while !open.is_empty() {
-            let current = open.pop().unwrap();
+    }
 
-            for neighbor in self.valid_neighbors_backwards(current.location) {
-                let alt = distance
-                    .get(&current.location)
-                    .map(|d| d + 1)
-                    .unwrap_or(usize::MAX);
+    /// The cost of a step from `from` to `to` height.
+    pub fn cost(&self, from: u8, to: u8) -> usize {
+        1 + self.height_cost * from.abs_diff(to) as usize
+    }
+}
 
-                if alt < *distance.get(&neighbor).unwrap_or(&usize::MAX) {
-                    distance.insert(neighbor, alt);
-                    came_from.insert(neighbor, current.location);
-                    open.push(HeapEntry::new(neighbor, alt));
-                }
-            }
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            max_ascent: 1,
+            max_descent: None,
+            diagonals: false,
+            height_cost: 0,
         }
-
-        came_from
     }
 }
 
-fn reconstruct_path(
-    came_from: &HashMap<(usize, usize), (usize, usize)>,
-    end: (usize, usize),
-) -> Vec<(usize, usize)> {
-    let mut path = vec![end];
-    let mut current = &end;
+#[derive(Debug)]
+pub struct HeightMap {
+    map: Grid<u8>,
+    start: Vector2<isize>,
+    end: Vector2<isize>,
+}
 
-    while let Some(prev) = came_from.get(current) {
-        path.push(*prev);
-        current = prev;
+impl HeightMap {
+    /// The cheapest path to the end from whichever of `starts` is closest.
+    pub fn shortest_path<I>(&self, rules: &Rules, starts: I) -> Option<Vec<Vector2<isize>>>
+    where
+        I: IntoIterator<Item = Vector2<isize>>,
+    {
+        let result = search::dijkstra(
+            starts,
+            |&position| {
+                let height = self[position];
+
+                self.neighbours(rules, position)
+                    .into_iter()
+                    .filter(move |&(_, next)| rules.allows(height, next))
+                    .map(move |(neighbour, next)| (neighbour, rules.cost(height, next)))
+            },
+            |&position| position == self.end,
+        );
+
+        result.path_to(result.goal()?)
     }
 
-    path.reverse();
-
-    path
-}
+    /// The cost of getting from every cell to the end, the number of steps with the default
+    /// rules.
+    ///
+    /// A single search backwards from the end, stepping to neighbours that could move to the
+    /// current cell.
+    pub fn distance_field(&self, rules: &Rules) -> DistanceField {
+        let result = search::dijkstra(
+            [self.end],
+            |&position| {
+                let height = self[position];
+
+                self.neighbours(rules, position)
+                    .into_iter()
+                    .filter(move |&(_, previous)| rules.allows(previous, height))
+                    .map(move |(neighbour, previous)| (neighbour, rules.cost(previous, height)))
+            },
+            |_| false,
+        );
+        let distances = self
+            .map
+            .positions()
+            .map(|position| result.distance(&position))
+            .collect();
 
-impl Index<(usize, usize)> for HeightMap {
-    type Output = u8;
+        DistanceField {
+            distances: Grid::new(self.map.width(), self.map.height(), distances),
+        }
+    }
 
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        &self.map[index.1][index.0]
+    /// The neighbours of `position` the rules allow moving in the direction of, with their
+    /// heights.
+    fn neighbours(&self, rules: &Rules, position: Vector2<isize>) -> Vec<(Vector2<isize>, u8)> {
+        if rules.diagonals {
+            self.map
+                .neighbours8(position)
+                .map(|(p, &height)| (p, height))
+                .collect()
+        } else {
+            self.map
+                .neighbours4(position)
+                .map(|(p, &height)| (p, height))
+                .collect()
+        }
     }
 }
 
-impl FromStr for HeightMap {
-    type Err = String;
+/// Characters of the heat map, from furthest from the end to closest.
+const HEAT: [char; 9] = ['.', ':', '-', '=', '+', '*', '#', '%', '@'];
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut start: Option<(usize, usize)> = None;
-        let mut end: Option<(usize, usize)> = None;
-
-        let map: Result<Vec<Vec<u8>>, String> = s
-            .trim()
-            .lines()
-            .enumerate()
-            .map(|(y, line)| {
-                line.trim()
-                    .chars()
-                    .enumerate()
-                    .map(|(x, c)| match c {
-                        'a'..='z' => Ok((c as u8) - 97),
-                        'S' => {
-                            start = Some((x, y));
-
-                            Ok(b'a' - 97)
-                        }
-                        'E' => {
-                            end = Some((x, y));
-
-                            Ok(b'z' - 97)
-                        }
-                        _ => Err(format!("Unexpected character: '{c}' in {line}")),
-                    })
-                    .collect::<Result<Vec<u8>, String>>()
-            })
-            .collect();
+/// The cost of getting from every cell of a [`HeightMap`] to its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceField {
+    /// [`None`] for cells the end can't be reached from.
+    distances: Grid<Option<usize>>,
+}
 
-        let start = start.ok_or_else(|| "No start location found".to_owned())?;
-        let end = end.ok_or_else(|| "No end location found".to_owned())?;
+impl DistanceField {
+    /// The cost from `position` to the end, [`None`] if the end can't be reached from there or
+    /// it's outside the map.
+    pub fn distance_from(&self, position: Vector2<isize>) -> Option<usize> {
+        self.distances.get(position).copied().flatten()
+    }
 
-        Ok(Self {
-            map: map?,
-            start,
-            end,
+    /// Render the distances as characters, hotter closer to the end, and a space where the end
+    /// can't be reached.
+    pub fn heat_map(&self) -> Grid<char> {
+        let furthest = self
+            .distances
+            .iter()
+            .filter_map(|(_, &d)| d)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        self.distances.map(|&distance| match distance {
+            Some(d) => HEAT[(furthest - d.min(furthest)) * (HEAT.len() - 1) / furthest],
+            None => ' ',
         })
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct HeapEntry<T> {
-    location: (usize, usize),
-    value: T,
-}
+impl Index<Vector2<isize>> for HeightMap {
+    type Output = u8;
 
-impl<T: Ord> HeapEntry<T> {
-    fn new(location: (usize, usize), value: T) -> Self {
-        Self { location, value }
+    fn index(&self, index: Vector2<isize>) -> &Self::Output {
+        &self.map[index]
     }
 }
 
-impl<T: Ord> Ord for HeapEntry<T> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other
-            .value
-            .cmp(&self.value)
-            .then_with(|| self.location.cmp(&other.location))
-    }
-}
-impl<T: Ord> PartialOrd for HeapEntry<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl FromStr for HeightMap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tiles = Grid::parse(s, |c| matches!(c, 'a'..='z' | 'S' | 'E').then_some(c))?;
+        let find = |tile: char| {
+            tiles
+                .iter()
+                .find_map(|(position, &c)| (c == tile).then_some(position))
+        };
+
+        let start = find('S').ok_or("No start location found")?;
+        let end = find('E').ok_or("No end location found")?;
+        let map = tiles.map(|&c| match c {
+            'S' => 0,
+            'E' => b'z' - b'a',
+            c => (c as u8) - b'a',
+        });
+
+        Ok(Self { map, start, end })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BinaryHeap;
-
-    use super::{star_one, star_two, HeapEntry};
-    static INPUT: &'static str = r#"
+    use super::{star_one, star_two, Day12, Rules};
+    use crate::{math::Vector2, Error, Location, Solution};
+    static INPUT: &str = r#"
 Sabqponm
 abcryxxl
 accszExk
@@ -245,4 +248,86 @@ abdefghi"#;
     fn test_star_two() {
         assert_eq!(star_two(INPUT), 29);
     }
+
+    #[test]
+    fn test_parse_error_location() {
+        let input = INPUT.replace("accszExk", "accs?Exk");
+
+        let Err(Error::Parse(e)) = Day12::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(4, 5));
+        assert_eq!(e.text, "?");
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let map = Day12::parse(INPUT).unwrap();
+        let field = map.distance_field(&Rules::default());
+
+        assert_eq!(field.distance_from(map.start), Some(31));
+        assert_eq!(field.distance_from(map.end), Some(0));
+        assert_eq!(field.distance_from(Vector2::new(0, 4)), Some(29));
+        assert_eq!(field.distance_from(Vector2::new(8, 0)), None);
+
+        let heat_map = field.heat_map().to_string();
+        assert_eq!(heat_map.lines().count(), 5);
+        assert_eq!(heat_map.lines().nth(2).unwrap().chars().nth(5), Some('@'));
+        assert_eq!(heat_map.chars().next(), Some('.'));
+    }
+
+    #[test]
+    fn test_unreachable() {
+        // The end is walled in by cells that are too high
+        let map = Day12::parse("SazE\nabzz").unwrap();
+        let field = map.distance_field(&Rules::default());
+
+        assert_eq!(field.distance_from(map.start), None);
+        assert_eq!(field.heat_map().to_string(), "  +@\n  .+\n");
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::default();
+        assert!(rules.allows(3, 4));
+        assert!(!rules.allows(3, 5));
+        assert!(rules.allows(25, 0));
+        assert_eq!(rules.cost(25, 0), 1);
+
+        let rules = Rules {
+            max_descent: Some(2),
+            height_cost: 3,
+            ..Rules::default()
+        };
+        assert!(rules.allows(5, 3));
+        assert!(!rules.allows(5, 2));
+        assert_eq!(rules.cost(5, 3), 7);
+    }
+
+    #[test]
+    fn test_what_if() {
+        let map = Day12::parse(INPUT).unwrap();
+        let distance = |rules: Rules| map.distance_field(&rules).distance_from(map.start);
+
+        let diagonals = Rules {
+            diagonals: true,
+            ..Rules::default()
+        };
+        assert_eq!(distance(diagonals), Some(27));
+        let path = map.shortest_path(&diagonals, [map.start]).unwrap();
+        assert_eq!(path.len() - 1, 27);
+
+        // Every level has to be climbed at least once on the way up
+        let costly = Rules {
+            height_cost: 1,
+            ..Rules::default()
+        };
+        assert_eq!(distance(costly), Some(56));
+
+        let steep = Rules {
+            max_ascent: 25,
+            ..Rules::default()
+        };
+        assert_eq!(distance(steep), Some(7));
+    }
 }
@@ -4,76 +4,96 @@ use std::fmt::{self, Write};
 use std::ops::ControlFlow;
 use std::str::FromStr;
 
+use crate::{Error, Location, Solution};
+
 pub fn star_one(input: &str) -> usize {
-    let pairs = parse(input);
+    let pairs = Day13::parse(input).expect("Should be able to parse input");
 
-    pairs
-        .into_iter()
-        .enumerate()
-        .filter_map(|(i, p)| p.in_order().then_some((p, i + 1)))
-        .map(|(_, i)| i)
-        .sum()
+    Day13::part_one(&pairs)
 }
 
 pub fn star_two(input: &str) -> usize {
-    let dividers = [
-        Item::List(Item::parse_items("[[2]]").unwrap()),
-        Item::List(Item::parse_items("[[6]]").unwrap()),
-    ];
-    let ordered = {
-        let pairs = parse(input);
-        let mut list: Vec<_> = pairs
-            .into_iter()
-            .flat_map(|p| [p.first, p.second].into_iter())
-            .collect();
-        list.extend_from_slice(&dividers);
-
-        list.sort_by(|a, b| {
-            if in_order(a, b) {
-                Ordering::Less
-            } else if in_order(b, a) {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
-            }
-        });
+    let pairs = Day13::parse(input).expect("Should be able to parse input");
 
-        list
-    };
+    Day13::part_two(&pairs)
+}
 
-    let mut iter = ordered
-        .into_iter()
-        .enumerate()
-        .filter_map(|(i, it)| (it == dividers[0] || it == dividers[1]).then_some(i + 1));
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Vec<Pair>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+
+    fn part_one(pairs: &Self::Input) -> Self::PartOne {
+        pairs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.in_order().then_some((p, i + 1)))
+            .map(|(_, i)| i)
+            .sum()
+    }
+
+    fn part_two(pairs: &Self::Input) -> Self::PartTwo {
+        let dividers = [
+            Item::List(Item::parse_items("[[2]]").unwrap()),
+            Item::List(Item::parse_items("[[6]]").unwrap()),
+        ];
+        let ordered = {
+            let mut list: Vec<_> = pairs
+                .iter()
+                .flat_map(|p| [p.first.clone(), p.second.clone()].into_iter())
+                .collect();
+            list.extend_from_slice(&dividers);
+
+            list.sort_by(|a, b| {
+                if in_order(a, b) {
+                    Ordering::Less
+                } else if in_order(b, a) {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            });
+
+            list
+        };
 
-    iter.next().unwrap() * iter.next().unwrap()
+        let mut iter = ordered
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, it)| (it == dividers[0] || it == dividers[1]).then_some(i + 1));
+
+        iter.next().unwrap() * iter.next().unwrap()
+    }
 }
 
-fn parse(input: &str) -> Vec<Pair> {
+fn parse(input: &str) -> Result<Vec<Pair>, Error> {
     input
         .lines()
         .map(str::trim)
         .filter(|l| !l.is_empty())
-        .map(Item::parse_items)
+        .map(|l| Item::parse_items(l).map_err(|e| e.offset(Location::of(input, l), l)))
         .chunks(2)
         .into_iter()
         .map(|mut iter| {
-            let first = iter.next().unwrap();
-            let second = iter.next().unwrap();
-
-            first.and_then(|first| {
-                second.map(|second| Pair {
-                    first: Item::List(first),
-                    second: Item::List(second),
-                })
+            let first = iter.next().unwrap()?;
+            let second = iter.next().ok_or("Packet is missing its pair")??;
+
+            Ok(Pair {
+                first: Item::List(first),
+                second: Item::List(second),
             })
         })
-        .collect::<Result<Vec<Pair>, String>>()
-        .expect("Should be able to parse input")
+        .collect()
 }
 
 #[derive(Debug)]
-struct Pair {
+pub struct Pair {
     first: Item,
     second: Item,
 }
@@ -118,15 +138,17 @@ fn in_order_recurse(left: &Item, right: &Item) -> ControlFlow<bool, ()> {
     }
 }
 impl FromStr for Pair {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (s1, s2) = s
             .split_once('\n')
-            .ok_or_else(|| format!("Invalid packet pair:\n{s}"))?;
+            .ok_or_else(|| Error::parse(s, s, "Invalid packet pair"))?;
 
-        let first = Item::List(Item::parse_items(s1)?);
-        let second = Item::List(Item::parse_items(s2)?);
+        let first =
+            Item::List(Item::parse_items(s1).map_err(|e| e.offset(Location::of(s, s1), s1))?);
+        let second =
+            Item::List(Item::parse_items(s2).map_err(|e| e.offset(Location::of(s, s2), s2))?);
 
         Ok(Self { first, second })
     }
@@ -139,7 +161,7 @@ enum Item {
 }
 
 impl Item {
-    fn parse_items(s: &str) -> Result<Vec<Self>, String> {
+    fn parse_items(s: &str) -> Result<Vec<Self>, Error> {
         let list = parse_list(s)?;
 
         Ok(list)
@@ -169,13 +191,13 @@ impl fmt::Debug for Item {
 }
 
 impl FromStr for Item {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.chars().all(|c| c.is_ascii_digit()) {
             let parsed: u64 = s
                 .parse()
-                .map_err(|e| format!("Failed to parse `{s}` as u64: {e}"))?;
+                .map_err(|e| Error::parse(s, s, format!("Failed to parse as u64: {e}")))?;
 
             Ok(Item::Integer(parsed))
         } else {
@@ -186,13 +208,21 @@ impl FromStr for Item {
     }
 }
 
-fn parse_list(s: &str) -> Result<Vec<Item>, String> {
-    let s = s.strip_prefix('[').ok_or_else(|| {
-        format!("Expected packet sequence to start with [, but didn't find this in: `{s}`")
+fn parse_list(original: &str) -> Result<Vec<Item>, Error> {
+    let s = original.strip_prefix('[').ok_or_else(|| {
+        Error::parse(
+            original,
+            original,
+            "Expected packet sequence to start with [",
+        )
     })?;
     let s = s
         .strip_suffix(']')
-        .ok_or_else(|| "Expected sequence to end with ]".to_string())?;
+        .ok_or_else(|| Error::parse(original, original, "Expected sequence to end with ]"))?;
+    let parse_item = |item: &str| {
+        item.parse()
+            .map_err(|e: Error| e.offset(Location::of(original, item), item))
+    };
 
     let mut result = vec![];
     let mut s = s;
@@ -202,16 +232,16 @@ fn parse_list(s: &str) -> Result<Vec<Item>, String> {
             let mut parts = s.splitn(2, ',');
             let digit = parts
                 .next()
-                .ok_or_else(|| format!("Failed to parse leadig number from {}", s))?;
+                .ok_or_else(|| Error::parse(original, s, "Failed to parse leading number"))?;
             let rest = parts.next().unwrap_or("");
 
-            result.push(digit.parse()?);
+            result.push(parse_item(digit.trim_end())?);
             s = rest;
         } else if s.starts_with('[') {
             let (item, rest) = slice_balanced(s, ('[', ']'))
-                .ok_or_else(|| format!("Could not find sublist in `{s}`"))?;
+                .ok_or_else(|| Error::parse(original, s, "Could not find sublist"))?;
 
-            result.push(item.parse()?);
+            result.push(parse_item(item)?);
 
             s = rest;
             if let Some(rest) = s.strip_prefix(',') {
@@ -219,6 +249,12 @@ fn parse_list(s: &str) -> Result<Vec<Item>, String> {
             }
 
             assert!(s != "]");
+        } else if !s.is_empty() {
+            return Err(Error::parse(
+                original,
+                s,
+                "Expected either a number or a list",
+            ));
         }
     }
 
@@ -261,7 +297,7 @@ fn break_value<B, C>(c: ControlFlow<B, C>) -> Option<B> {
 mod tests {
     use super::*;
 
-    static INPUT: &'static str = r#"
+    static INPUT: &str = r#"
         [1,1,3,1,1]
         [1,1,5,1,1]
 
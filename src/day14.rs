@@ -1,39 +1,65 @@
 use core::fmt;
-use std::collections::HashMap;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
 use itertools::Itertools;
 
+use crate::grid::SparseGrid;
 use crate::math::Vector2;
+use crate::{Error, Solution};
 
 pub fn star_one(input: &str) -> usize {
-    let mut map: Map = input.parse().expect("Should be able to parse the map");
+    let map = Day14::parse(input).expect("Should be able to parse the map");
 
-    while map.tick() {}
-
-    map.sand_at_rest()
+    Day14::part_one(&map)
 }
 
 pub fn star_two(input: &str) -> usize {
-    let mut map: Map = input.parse().expect("Should be able to parse the map");
-    map.mode = Mode::Floor;
+    let map = Day14::parse(input).expect("Should be able to parse the map");
+
+    Day14::part_two(&map)
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Map;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.parse::<Map>()?)
+    }
 
-    while map.tick() {}
+    fn part_one(map: &Self::Input) -> Self::PartOne {
+        let mut map = map.clone();
 
-    map.sand_at_rest()
+        while map.tick() {}
+
+        map.sand_at_rest()
+    }
+
+    fn part_two(map: &Self::Input) -> Self::PartTwo {
+        let mut map = map.clone();
+        map.mode = Mode::Floor;
+
+        while map.tick() {}
+
+        map.sand_at_rest()
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Mode {
     Abyss,
     Floor,
 }
 
-struct Map {
+#[derive(Clone)]
+pub struct Map {
     /// The locations occupied by something meaningful.
     /// This is sparse, air is absent.
-    locations: HashMap<Vector2<isize>, Location>,
+    locations: SparseGrid<Location>,
     /// The point in the y axis at which sand just falls into the abyss
     max_y: isize,
     spawn_location: Vector2<isize>,
@@ -41,16 +67,8 @@ struct Map {
 }
 
 impl Map {
-    fn new(
-        locations: HashMap<Vector2<isize>, Location>,
-        spawn_location: Vector2<isize>,
-        mode: Mode,
-    ) -> Self {
-        let max_y = locations
-            .keys()
-            .max_by_key(|p| p.y)
-            .map(|p| p.y)
-            .unwrap_or(0);
+    fn new(locations: SparseGrid<Location>, spawn_location: Vector2<isize>, mode: Mode) -> Self {
+        let max_y = locations.bounds().map(|(_, max)| max.y).unwrap_or(0);
 
         Self {
             locations,
@@ -73,7 +91,7 @@ impl Map {
 
             let Some(fall_direction) = self.fall_location(location) else {
                 // Came to rest
-                break
+                break;
             };
 
             location = fall_direction;
@@ -82,41 +100,25 @@ impl Map {
         self.locations.insert(location, Location::Sand);
 
         if self.mode == Mode::Floor {
-            self.locations.get(&self.spawn_location).is_none()
+            !self.locations.contains(self.spawn_location)
         } else {
             true
         }
     }
 
     /// Where will a given unit of sand fall to.
+    ///
+    /// Sand tries straight down first, then down and to the left, then down and to the right.
     fn fall_location(&self, location: Vector2<isize>) -> Option<Vector2<isize>> {
         let floor = self.max_y + 2;
 
-        // Down
-        let candidate = Vector2::new(location.x, location.y + 1);
-        if !self.locations.contains_key(&candidate)
-            && (self.mode == Mode::Abyss || floor != candidate.y)
-        {
-            return Some(candidate);
-        }
-
-        // Left
-        let candidate = Vector2::new(location.x - 1, location.y + 1);
-        if !self.locations.contains_key(&candidate)
-            && (self.mode == Mode::Abyss || floor != candidate.y)
-        {
-            return Some(candidate);
-        }
-
-        // Right
-        let candidate = Vector2::new(location.x + 1, location.y + 1);
-        if !self.locations.contains_key(&candidate)
-            && (self.mode == Mode::Abyss || floor != candidate.y)
-        {
-            return Some(candidate);
-        }
-
-        None
+        [(0, 1), (-1, 1), (1, 1)]
+            .into_iter()
+            .map(|offset| location + offset.into())
+            .find(|&candidate| {
+                !self.locations.contains(candidate)
+                    && (self.mode == Mode::Abyss || floor != candidate.y)
+            })
     }
 
     fn sand_at_rest(&self) -> usize {
@@ -133,7 +135,7 @@ impl FromStr for Map {
             .map(str::trim)
             .filter(|l| !l.is_empty())
             .flat_map(parse_rocks_in_line)
-            .collect::<Result<HashMap<_, _>, String>>()?;
+            .collect::<Result<SparseGrid<_>, String>>()?;
 
         Ok(Self::new(locations, Vector2::new(500, 0), Mode::Abyss))
     }
@@ -197,19 +199,22 @@ impl Location {
     }
 }
 
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Rock => write!(f, "#"),
+            Location::Sand => write!(f, "O"),
+        }
+    }
+}
+
 const DEBUG_PADDING: isize = 2;
 impl fmt::Debug for Map {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let min = Vector2::new(
-            self.locations.keys().map(|p| p.x).min().unwrap_or(0) - DEBUG_PADDING,
-            self.locations.keys().map(|p| p.y).min().unwrap_or(0) - DEBUG_PADDING,
-        );
-        let max = Vector2::new(
-            self.locations.keys().map(|p| p.x).max().unwrap_or(0) + DEBUG_PADDING,
-            self.locations.keys().map(|p| p.y).max().unwrap_or(0)
-                + DEBUG_PADDING
-                + isize::from(self.mode == Mode::Floor) * 2,
-        );
+        let (min, max) = self.locations.bounds().unwrap_or_default();
+        let padding = Vector2::new(DEBUG_PADDING, DEBUG_PADDING);
+        let min = min - padding;
+        let max = max + padding + Vector2::new(0, isize::from(self.mode == Mode::Floor) * 2);
 
         for y in min.y..=max.y {
             for x in min.x..=max.x {
@@ -218,9 +223,8 @@ impl fmt::Debug for Map {
                     continue;
                 }
 
-                match self.locations.get(&Vector2::new(x, y)) {
-                    Some(Location::Rock) => write!(f, "#")?,
-                    Some(Location::Sand) => write!(f, "O")?,
+                match self.locations.get(Vector2::new(x, y)) {
+                    Some(location) => write!(f, "{location}")?,
                     None => write!(f, ".")?,
                 }
             }
@@ -234,7 +238,7 @@ impl fmt::Debug for Map {
 #[cfg(test)]
 mod tests {
     use super::{star_one, star_two};
-    static INPUT: &'static str = r#"
+    static INPUT: &str = r#"
 498,4 -> 498,6 -> 496,6
 503,4 -> 502,4 -> 502,9 -> 494,9
 "#;
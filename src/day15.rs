@@ -1,22 +1,295 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::{math::Vector2, try_parse_lines, Error, Solution};
+
+/// The row to check in part one.
+const ROW: isize = 2_000_000;
+/// The distress beacon's coordinates are between 0 and this, inclusive.
+const SEARCH_SIZE: isize = 4_000_000;
+
+pub fn star_one(input: &str) -> usize {
+    let sensors = Day15::parse(input).expect("Failed to parse input");
+
+    Day15::part_one(&sensors)
+}
+
+pub fn star_two(input: &str) -> isize {
+    let sensors = Day15::parse(input).expect("Failed to parse input");
+
+    Day15::part_two(&sensors)
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Vec<Sensor>;
+    type PartOne = usize;
+    type PartTwo = isize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines(input).collect()
+    }
+
+    fn part_one(sensors: &Self::Input) -> Self::PartOne {
+        positions_without_beacon(sensors, ROW)
+    }
+
+    fn part_two(sensors: &Self::Input) -> Self::PartTwo {
+        let beacon =
+            distress_beacon(sensors, SEARCH_SIZE).expect("Expected to find the distress beacon");
+
+        tuning_frequency(beacon)
+    }
+}
+
+/// The number of positions in `row` where the distress beacon can't be.
+fn positions_without_beacon(sensors: &[Sensor], row: isize) -> usize {
+    let covered = merge(sensors.iter().filter_map(|s| s.coverage(row)).collect());
+    let beacons: HashSet<_> = sensors
+        .iter()
+        .map(|s| s.beacon)
+        .filter(|b| {
+            b.y == row
+                && covered
+                    .iter()
+                    .any(|&(start, end)| (start..=end).contains(&b.x))
+        })
+        .collect();
+
+    let positions: isize = covered.iter().map(|(start, end)| end - start + 1).sum();
+
+    positions as usize - beacons.len()
+}
+
+/// Find the only position with both coordinates between 0 and `size` that no sensor covers.
+///
+/// Searching happens in coordinates rotated by 45°, `u = x + y` and `v = x - y`, where every
+/// sensor covers an axis aligned square. The distress beacon has a covered neighbour, so it's
+/// just out of range of that neighbour's sensor and lies on one of the four lines bordering its
+/// square. Only these lines have to be searched, each one with interval merging.
+fn distress_beacon(sensors: &[Sensor], size: isize) -> Option<Vector2<isize>> {
+    sensors
+        .iter()
+        .flat_map(|sensor| {
+            let (center, r) = (rotate(sensor.position), sensor.radius + 1);
+
+            [
+                Line::U(center.x - r),
+                Line::U(center.x + r),
+                Line::V(center.y - r),
+                Line::V(center.y + r),
+            ]
+        })
+        .find_map(|line| line.search(sensors, size))
+}
+
+fn tuning_frequency(beacon: Vector2<isize>) -> isize {
+    beacon.x * 4_000_000 + beacon.y
+}
+
+/// A diagonal line in rotated coordinates, see [`distress_beacon`].
+#[derive(Debug, Copy, Clone)]
+enum Line {
+    /// All points with `x + y` equal to the value.
+    U(isize),
+    /// All points with `x - y` equal to the value.
+    V(isize),
+}
+
+impl Line {
+    /// The first position on this line that no sensor covers, within `0..=size` on both axes.
+    fn search(self, sensors: &[Sensor], size: isize) -> Option<Vector2<isize>> {
+        let (fixed, start, end) = match self {
+            Line::U(u) => (u, (-u).max(u - 2 * size), u.min(2 * size - u)),
+            Line::V(v) => (v, (-v).max(v), (2 * size - v).min(2 * size + v)),
+        };
+        let covered = merge(
+            sensors
+                .iter()
+                .filter_map(|sensor| {
+                    let center = rotate(sensor.position);
+                    let (along, across) = match self {
+                        Line::U(_) => (center.y, center.x),
+                        Line::V(_) => (center.x, center.y),
+                    };
+
+                    ((fixed - across).abs() <= sensor.radius)
+                        .then_some((along - sensor.radius, along + sensor.radius))
+                })
+                .collect(),
+        );
+
+        // Only coordinates with the same parity as the fixed one map back to whole positions
+        let align = |value: isize| value + (value - fixed).rem_euclid(2);
+        let mut candidate = align(start);
+
+        for (from, to) in covered {
+            if candidate < from {
+                break;
+            }
+
+            candidate = candidate.max(align(to + 1));
+        }
+
+        (candidate <= end).then(|| match self {
+            Line::U(u) => unrotate(Vector2::new(u, candidate)),
+            Line::V(v) => unrotate(Vector2::new(candidate, v)),
+        })
+    }
+}
+
+fn rotate(p: Vector2<isize>) -> Vector2<isize> {
+    Vector2::new(p.x + p.y, p.x - p.y)
+}
+
+/// The inverse of [`rotate`], rotating twice scales by two.
+fn unrotate(p: Vector2<isize>) -> Vector2<isize> {
+    rotate(p) / 2
+}
+
+/// Merge inclusive intervals that overlap or touch, sorted by start.
+fn merge(mut intervals: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+    intervals.sort_unstable();
+
+    intervals
+        .into_iter()
+        .fold(vec![], |mut merged, (start, end)| {
+            match merged.last_mut() {
+                Some((_, last_end)) if start <= *last_end + 1 => *last_end = (*last_end).max(end),
+                _ => merged.push((start, end)),
+            }
+
+            merged
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sensor {
+    position: Vector2<isize>,
+    /// The closest beacon.
+    beacon: Vector2<isize>,
+    /// Distance to the closest beacon, there are no other beacons within it.
+    radius: isize,
+}
+
+impl Sensor {
+    pub fn new(position: Vector2<isize>, beacon: Vector2<isize>) -> Self {
+        Self {
+            position,
+            beacon,
+            radius: position.manhattan_distance(beacon),
+        }
+    }
+
+    /// The inclusive range of columns in `row` within range of the sensor.
+    fn coverage(&self, row: isize) -> Option<(isize, isize)> {
+        let remaining = self.radius - (self.position.y - row).abs();
+
+        (remaining >= 0).then_some((self.position.x - remaining, self.position.x + remaining))
+    }
+}
+
+impl FromStr for Sensor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("Sensor at ")
+            .ok_or_else(|| Error::parse(s, s, "Expected `Sensor at`"))?;
+        let (position, beacon) = rest
+            .split_once(": closest beacon is at ")
+            .ok_or_else(|| Error::parse(s, rest, "Expected `: closest beacon is at`"))?;
+
+        Ok(Self::new(
+            parse_position(s, position)?,
+            parse_position(s, beacon)?,
+        ))
+    }
+}
+
+/// Parse `part` of `s`, formatted like `x=2, y=-18`.
+fn parse_position(s: &str, part: &str) -> Result<Vector2<isize>, Error> {
+    let (x, y) = part
+        .split_once(", ")
+        .ok_or_else(|| Error::parse(s, part, "Expected a position like `x=1, y=2`"))?;
+    let coordinate = |value: &str, prefix: &str| {
+        let value = value
+            .strip_prefix(prefix)
+            .ok_or_else(|| Error::parse(s, value, format!("Expected `{prefix}`")))?;
+
+        value
+            .parse()
+            .map_err(|e| Error::parse(s, value, format!("Invalid coordinate: {e}")))
+    };
+
+    Ok(Vector2::new(coordinate(x, "x=")?, coordinate(y, "y=")?))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{distress_beacon, merge, positions_without_beacon, tuning_frequency, Day15};
+    use crate::{math::Vector2, Error, Location, Solution};
+
+    const INPUT: &str = r#"
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        let sensors = Day15::parse(INPUT).unwrap();
+
+        assert_eq!(positions_without_beacon(&sensors, 10), 26);
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        let sensors = Day15::parse(INPUT).unwrap();
+        let beacon = distress_beacon(&sensors, 20);
+
+        assert_eq!(beacon, Some(Vector2::new(14, 11)));
+        assert_eq!(tuning_frequency(beacon.unwrap()), 56000011);
+    }
+
+    #[test]
+    fn test_distress_beacon_on_edge() {
+        // A single sensor covering everything but the corner at (4, 4)
+        let sensors = Day15::parse("Sensor at x=0, y=0: closest beacon is at x=7, y=0").unwrap();
+
+        assert_eq!(distress_beacon(&sensors, 4), Some(Vector2::new(4, 4)));
+        assert_eq!(distress_beacon(&sensors, 3), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_eq!(
+            merge(vec![(5, 8), (-2, 1), (2, 3), (0, 1), (10, 10)]),
+            vec![(-2, 3), (5, 8), (10, 10)]
+        );
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
+                     Sensor at x=9, y=1a: closest beacon is at x=10, y=16";
+
+        let Err(Error::Parse(e)) = Day15::parse(input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 18));
+        assert_eq!(e.text, "1a");
     }
 }
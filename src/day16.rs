@@ -1,22 +1,281 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{search, try_parse_lines, Error, Solution};
+
+const START: &str = "AA";
+
+pub fn star_one(input: &str) -> u32 {
+    let network = Day16::parse(input).expect("Failed to parse input");
+
+    Day16::part_one(&network)
+}
+
+pub fn star_two(input: &str) -> u32 {
+    let network = Day16::parse(input).expect("Failed to parse input");
+
+    Day16::part_two(&network)
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = Network;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let valves: Vec<Valve> = try_parse_lines(input).collect::<Result<_, _>>()?;
+
+        Network::new(&valves)
+    }
+
+    fn part_one(network: &Self::Input) -> Self::PartOne {
+        network
+            .best_per_set(30)
+            .into_values()
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn part_two(network: &Self::Input) -> Self::PartTwo {
+        // You and the elephant open disjoint sets of valves, so pair up the best results
+        let mut best: Vec<_> = network.best_per_set(26).into_iter().collect();
+        best.sort_unstable_by_key(|&(_, released)| Reverse(released));
+
+        let mut most = 0;
+        for (i, &(yours, released)) in best.iter().enumerate() {
+            for &(elephants, elephant_released) in &best[i..] {
+                if released + elephant_released <= most {
+                    // Sorted descending, nothing after this can do better
+                    break;
+                }
+
+                if yours & elephants == 0 {
+                    most = released + elephant_released;
+                }
+            }
+        }
+
+        most
+    }
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+/// The valves with a non-zero flow rate and the travel times between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    /// Flow rate of each useful valve, indexed like the bits in a set of opened valves.
+    flow_rates: Vec<u32>,
+    /// Minutes needed to get from one valve to another, the starting valve has the last index.
+    distances: Vec<Vec<u32>>,
+}
+
+impl Network {
+    /// Compress the full tunnel graph to only the valves worth opening, plus the start.
+    fn new(valves: &[Valve]) -> Result<Self, Error> {
+        let indices: HashMap<&str, usize> = valves
+            .iter()
+            .enumerate()
+            .map(|(idx, valve)| (valve.name.as_str(), idx))
+            .collect();
+        let neighbours = valves
+            .iter()
+            .map(|valve| {
+                valve
+                    .tunnels
+                    .iter()
+                    .map(|tunnel| {
+                        indices.get(tunnel.as_str()).copied().ok_or_else(|| {
+                            format!(
+                                "Valve {} leads to valve {tunnel}, which doesn't exist",
+                                valve.name
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let start = *indices
+            .get(START)
+            .ok_or_else(|| format!("Missing the starting valve {START}"))?;
+        let mut useful: Vec<usize> = (0..valves.len())
+            .filter(|&idx| valves[idx].flow_rate > 0)
+            .collect();
+
+        if useful.len() >= u64::BITS as usize {
+            return Err(format!(
+                "Can only handle up to {} valves with a non-zero flow rate, found {}",
+                u64::BITS - 1,
+                useful.len()
+            )
+            .into());
+        }
+
+        let flow_rates = useful.iter().map(|&idx| valves[idx].flow_rate).collect();
+        useful.push(start);
+        let distances = useful
+            .iter()
+            .map(|&from| {
+                let all = distances_from(&neighbours, from);
+
+                useful.iter().map(|&to| all[to]).collect()
+            })
+            .collect();
+
+        Ok(Self {
+            flow_rates,
+            distances,
+        })
+    }
+
+    /// The most pressure that can be released in `minutes` for every set of opened valves.
+    ///
+    /// Sets are bitmasks over the useful valves, sets that can't be opened in time are missing.
+    fn best_per_set(&self, minutes: u32) -> HashMap<u64, u32> {
+        let mut best = HashMap::new();
+        self.visit(self.flow_rates.len(), minutes, 0, 0, &mut best);
+
+        best
+    }
+
+    fn visit(
+        &self,
+        valve: usize,
+        remaining: u32,
+        opened: u64,
+        released: u32,
+        best: &mut HashMap<u64, u32>,
+    ) {
+        let entry = best.entry(opened).or_default();
+        *entry = (*entry).max(released);
+
+        for (next, &flow_rate) in self.flow_rates.iter().enumerate() {
+            let bit = 1 << next;
+            // Walking there and opening the valve
+            let cost = self.distances[valve][next].saturating_add(1);
+
+            if opened & bit != 0 || cost >= remaining {
+                continue;
+            }
+
+            let remaining = remaining - cost;
+            self.visit(
+                next,
+                remaining,
+                opened | bit,
+                released + remaining * flow_rate,
+                best,
+            );
+        }
+    }
+}
+
+/// Breadth first search from `start`, unreachable valves are [`u32::MAX`] away.
+fn distances_from(neighbours: &[Vec<usize>], start: usize) -> Vec<u32> {
+    let result = search::bfs(
+        [start],
+        |&valve| neighbours[valve].iter().copied(),
+        |_| false,
+    );
+
+    let mut distances = vec![u32::MAX; neighbours.len()];
+    for (&valve, &distance) in result.distances() {
+        distances[valve] = distance as u32;
+    }
+
+    distances
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Valve {
+    name: String,
+    flow_rate: u32,
+    tunnels: Vec<String>,
+}
+
+impl FromStr for Valve {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("Valve ")
+            .ok_or_else(|| Error::parse(s, s, "Expected `Valve`"))?;
+        let (name, rest) = rest
+            .split_once(" has flow rate=")
+            .ok_or_else(|| Error::parse(s, rest, "Expected `has flow rate=`"))?;
+        let (flow_rate, rest) = rest
+            .split_once(';')
+            .ok_or_else(|| Error::parse(s, rest, "Expected `;` after the flow rate"))?;
+        let flow_rate = flow_rate
+            .parse()
+            .map_err(|e| Error::parse(s, flow_rate, format!("Invalid flow rate: {e}")))?;
+
+        let rest = rest.trim_start();
+        let tunnels = ["tunnels lead to valves ", "tunnel leads to valve "]
+            .iter()
+            .find_map(|prefix| rest.strip_prefix(prefix))
+            .ok_or_else(|| Error::parse(s, rest, "Expected the tunnels leading from the valve"))?;
+
+        Ok(Self {
+            name: name.to_owned(),
+            flow_rate,
+            tunnels: tunnels.split(", ").map(ToOwned::to_owned).collect(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day16};
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = r#"
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 1651)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 1707)
+    }
+
+    #[test]
+    fn test_compressed_distances() {
+        let network = Day16::parse(INPUT).unwrap();
+
+        // BB, CC, DD, EE, HH and JJ are worth opening, AA comes last
+        assert_eq!(network.flow_rates, vec![13, 2, 20, 3, 22, 21]);
+        assert_eq!(network.distances[6], vec![1, 2, 1, 2, 5, 2, 0]);
+        assert_eq!(network.distances[4][5], 7);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT.replace("flow rate=3;", "flow rate=x;");
+        let Err(Error::Parse(e)) = Day16::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(6, 24));
+
+        let input = INPUT.replace("valves FF, DD", "valves FF, ZZ");
+        assert!(matches!(Day16::parse(&input), Err(Error::Invalid(_))));
+
+        let input = INPUT.replace("Valve AA", "Valve AB");
+        assert!(matches!(Day16::parse(&input), Err(Error::Invalid(_))));
     }
 }
@@ -1,22 +1,288 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::collections::HashMap;
+
+use crate::{Error, Solution};
+
+/// The chamber is seven units wide, the leftmost column is the highest bit of a row.
+const WIDTH: usize = 7;
+const LEFT_WALL: u8 = 1 << (WIDTH - 1);
+const RIGHT_WALL: u8 = 1;
+
+/// Rock shapes as rows from the bottom up, positioned two units from the left wall.
+const ROCKS: [&[u8]; 5] = [
+    &[0b0011110],
+    &[0b0001000, 0b0011100, 0b0001000],
+    &[0b0011100, 0b0000100, 0b0000100],
+    &[0b0010000, 0b0010000, 0b0010000, 0b0010000],
+    &[0b0011000, 0b0011000],
+];
+
+pub fn star_one(input: &str) -> u64 {
+    let jets = Day17::parse(input).expect("Failed to parse input");
+
+    Day17::part_one(&jets)
+}
+
+pub fn star_two(input: &str) -> u64 {
+    let jets = Day17::parse(input).expect("Failed to parse input");
+
+    Day17::part_two(&jets)
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Vec<Jet>;
+    type PartOne = u64;
+    type PartTwo = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let trimmed = input.trim();
+        let jets = trimmed
+            .char_indices()
+            .map(|(idx, c)| match c {
+                '<' => Ok(Jet::Left),
+                '>' => Ok(Jet::Right),
+                _ => Err(Error::parse(
+                    input,
+                    &trimmed[idx..idx + c.len_utf8()],
+                    format!("Unknown jet direction `{c}`"),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if jets.is_empty() {
+            return Err("Expected at least one jet".into());
+        }
+
+        Ok(jets)
+    }
+
+    fn part_one(jets: &Self::Input) -> Self::PartOne {
+        tower_height(jets, 2022)
+    }
+
+    fn part_two(jets: &Self::Input) -> Self::PartTwo {
+        tower_height(jets, 1_000_000_000_000)
+    }
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Jet {
+    Left,
+    Right,
+}
+
+/// The height of the tower after `rocks` rocks have come to rest.
+///
+/// Once the same rock is about to fall with the same jet up next onto the same surface, the
+/// tower is assumed to repeat itself. The height gained per repetition is then extrapolated
+/// instead of simulating every rock. See [`Chamber::surface`] for when that assumption could
+/// fail.
+fn tower_height(jets: &[Jet], rocks: u64) -> u64 {
+    let mut chamber = Chamber::new(jets);
+    // Tower height after each number of rocks
+    let mut heights = vec![0];
+    let mut seen = HashMap::new();
+
+    for dropped in 1..=rocks {
+        chamber.drop_rock();
+        heights.push(chamber.height() as u64);
+
+        let state = (chamber.rock, chamber.jet, chamber.surface());
+        if let Some(previous) = seen.insert(state, dropped) {
+            let cycle_length = dropped - previous;
+            let cycle_height = heights[dropped as usize] - heights[previous as usize];
+            let (cycles, remainder) = (
+                (rocks - dropped) / cycle_length,
+                (rocks - dropped) % cycle_length,
+            );
+            let partial = heights[(previous + remainder) as usize] - heights[previous as usize];
+
+            return heights[dropped as usize] + cycles * cycle_height + partial;
+        }
+    }
+
+    heights[rocks as usize]
+}
+
+struct Chamber<'a> {
+    /// Settled rocks from the floor up, one bit per unit.
+    rows: Vec<u8>,
+    jets: &'a [Jet],
+    /// Index of the next jet.
+    jet: usize,
+    /// Index of the next rock.
+    rock: usize,
+}
+
+impl<'a> Chamber<'a> {
+    fn new(jets: &'a [Jet]) -> Self {
+        Self {
+            rows: vec![],
+            jets,
+            jet: 0,
+            rock: 0,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn drop_rock(&mut self) {
+        let mut shape = ROCKS[self.rock].to_vec();
+        self.rock = (self.rock + 1) % ROCKS.len();
+        // Height of the rock's bottom row
+        let mut bottom = self.height() + 3;
+
+        loop {
+            let jet = self.jets[self.jet];
+            self.jet = (self.jet + 1) % self.jets.len();
+
+            let pushed: Option<Vec<u8>> = shape
+                .iter()
+                .map(|&row| match jet {
+                    Jet::Left => (row & LEFT_WALL == 0).then_some(row << 1),
+                    Jet::Right => (row & RIGHT_WALL == 0).then_some(row >> 1),
+                })
+                .collect();
+            if let Some(pushed) = pushed.filter(|p| !self.collides(p, bottom)) {
+                shape = pushed;
+            }
+
+            if bottom == 0 || self.collides(&shape, bottom - 1) {
+                break;
+            }
+            bottom -= 1;
+        }
+
+        for (offset, &row) in shape.iter().enumerate() {
+            let y = bottom + offset;
+            if y >= self.rows.len() {
+                self.rows.resize(y + 1, 0);
+            }
+
+            self.rows[y] |= row;
+        }
+    }
+
+    fn collides(&self, shape: &[u8], bottom: usize) -> bool {
+        shape
+            .iter()
+            .enumerate()
+            .any(|(offset, &row)| self.rows.get(bottom + offset).unwrap_or(&0) & row != 0)
+    }
+
+    /// How far below the top of the tower each column's highest settled unit is, columns that are
+    /// still empty count down to the floor.
+    ///
+    /// This is only a heuristic fingerprint of the tower's top. It ignores gaps and overhangs
+    /// below each column's highest unit, which a rock can still slide into sideways. Two chambers
+    /// with the same surface are assumed to behave the same for every rock to come, but the
+    /// shapes a rock can actually reach might differ.
+    fn surface(&self) -> [usize; WIDTH] {
+        let mut depths = [self.height(); WIDTH];
+
+        for (column, depth) in depths.iter_mut().enumerate() {
+            let bit = LEFT_WALL >> column;
+
+            if let Some(d) = self.rows.iter().rev().position(|row| row & bit != 0) {
+                *depth = d;
+            }
+        }
+
+        depths
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, tower_height, Chamber, Day17, Jet};
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 3068)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 1514285714288)
+    }
+
+    #[test]
+    fn test_first_rocks() {
+        let jets = Day17::parse(INPUT).unwrap();
+        let mut chamber = Chamber::new(&jets);
+
+        for _ in 0..3 {
+            chamber.drop_rock();
+        }
+
+        assert_eq!(
+            chamber.rows,
+            vec![0b0011110, 0b0001000, 0b0011100, 0b1111000, 0b0010000, 0b0010000]
+        );
+    }
+
+    #[test]
+    fn test_cycle_skipping() {
+        let jets = Day17::parse(INPUT).unwrap();
+        let mut chamber = Chamber::new(&jets);
+
+        for rocks in 1..=5000 {
+            chamber.drop_rock();
+
+            if rocks % 499 == 0 {
+                assert_eq!(
+                    tower_height(&jets, rocks),
+                    chamber.height() as u64,
+                    "After {rocks} rocks"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle_skipping_random_jets() {
+        // Irregular jet patterns, so the surface heuristic is checked on more than one tower
+        for seed in [1_u64, 7, 42] {
+            let mut state = seed;
+            let jets: Vec<_> = (0..101)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    if state >> 63 == 0 {
+                        Jet::Left
+                    } else {
+                        Jet::Right
+                    }
+                })
+                .collect();
+            let mut chamber = Chamber::new(&jets);
+
+            for rocks in 1..=3000 {
+                chamber.drop_rock();
+
+                if rocks % 97 == 0 {
+                    assert_eq!(
+                        tower_height(&jets, rocks),
+                        chamber.height() as u64,
+                        "After {rocks} rocks with seed {seed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let Err(Error::Parse(e)) = Day17::parse("<<>x>") else {
+            panic!("Expected a parse error");
+        };
+
+        assert_eq!(e.location, Location::new(1, 4));
+        assert!(Day17::parse("\n").is_err());
     }
 }
@@ -1,22 +1,181 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::collections::{HashSet, VecDeque};
+
+use crate::{math::Vector3, Error, Solution};
+
+const NEIGHBOURS: [Vector3<isize>; 6] = [
+    Vector3 { x: 1, y: 0, z: 0 },
+    Vector3 { x: -1, y: 0, z: 0 },
+    Vector3 { x: 0, y: 1, z: 0 },
+    Vector3 { x: 0, y: -1, z: 0 },
+    Vector3 { x: 0, y: 0, z: 1 },
+    Vector3 { x: 0, y: 0, z: -1 },
+];
+
+pub fn star_one(input: &str) -> usize {
+    let droplet = Day18::parse(input).expect("Failed to parse input");
+
+    Day18::part_one(&droplet)
+}
+
+pub fn star_two(input: &str) -> usize {
+    let droplet = Day18::parse(input).expect("Failed to parse input");
+
+    Day18::part_two(&droplet)
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Droplet;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let cubes = input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| parse_cube(input, l))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Droplet { cubes })
+    }
+
+    fn part_one(droplet: &Self::Input) -> Self::PartOne {
+        droplet.surface_area()
+    }
+
+    fn part_two(droplet: &Self::Input) -> Self::PartTwo {
+        droplet.exterior_surface_area()
+    }
+}
+
+/// A droplet of lava, scanned as 1x1x1 cubes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Droplet {
+    cubes: HashSet<Vector3<isize>>,
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+impl Droplet {
+    /// Faces not connected to another cube, including those of air pockets.
+    fn surface_area(&self) -> usize {
+        self.cubes
+            .iter()
+            .flat_map(|&cube| NEIGHBOURS.map(|n| cube + n))
+            .filter(|neighbour| !self.cubes.contains(neighbour))
+            .count()
+    }
+
+    /// Faces reachable by steam from outside the droplet.
+    ///
+    /// Flood fills the air in a box one unit larger than the droplet on every side, counting
+    /// every face of a cube the steam runs into.
+    fn exterior_surface_area(&self) -> usize {
+        let Some((min, max)) = self.bounds() else {
+            return 0;
+        };
+        let (min, max) = (min - Vector3::new(1, 1, 1), max + Vector3::new(1, 1, 1));
+        let in_bounds = |p: &Vector3<isize>| {
+            (min.x..=max.x).contains(&p.x)
+                && (min.y..=max.y).contains(&p.y)
+                && (min.z..=max.z).contains(&p.z)
+        };
+
+        let mut steam = HashSet::from([min]);
+        let mut queue = VecDeque::from([min]);
+        let mut faces = 0;
+
+        while let Some(current) = queue.pop_front() {
+            for next in NEIGHBOURS.map(|n| current + n) {
+                if self.cubes.contains(&next) {
+                    faces += 1;
+                } else if in_bounds(&next) && steam.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        faces
+    }
+
+    /// The smallest and largest coordinates on each axis, [`None`] without any cubes.
+    fn bounds(&self) -> Option<(Vector3<isize>, Vector3<isize>)> {
+        let first = *self.cubes.iter().next()?;
+
+        Some(self.cubes.iter().fold((first, first), |(min, max), c| {
+            (
+                Vector3::new(min.x.min(c.x), min.y.min(c.y), min.z.min(c.z)),
+                Vector3::new(max.x.max(c.x), max.y.max(c.y), max.z.max(c.z)),
+            )
+        }))
+    }
+}
+
+/// Parse a cube like `2,2,2` from `line`, a sub slice of `input`.
+fn parse_cube(input: &str, line: &str) -> Result<Vector3<isize>, Error> {
+    let coordinates = line
+        .split(',')
+        .map(|c| {
+            let c = c.trim();
+
+            c.parse()
+                .map_err(|e| Error::parse(input, c, format!("Invalid coordinate: {e}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match coordinates[..] {
+        [x, y, z] => Ok(Vector3::new(x, y, z)),
+        _ => Err(Error::parse(
+            input,
+            line,
+            "Expected three coordinates like `1,2,3`",
+        )),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day18};
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = r#"
+2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 64);
+        assert_eq!(star_one("1,1,1\n2,1,1"), 10);
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 58);
+        assert_eq!(star_two(""), 0);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let Err(Error::Parse(e)) = Day18::parse("1,2,3\n4,x,6") else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 3));
+
+        let Err(Error::Parse(e)) = Day18::parse("1,2,3\n4,5") else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 1));
     }
 }
@@ -1,22 +1,308 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::str::FromStr;
+use std::thread;
+
+use crate::{Error, Location, Solution};
+
+const ORE: usize = 0;
+const GEODE: usize = 3;
+const MINERALS: [&str; 4] = ["ore", "clay", "obsidian", "geode"];
+
+pub fn star_one(input: &str) -> u32 {
+    let blueprints = Day19::parse(input).expect("Failed to parse input");
+
+    Day19::part_one(&blueprints)
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+pub fn star_two(input: &str) -> u32 {
+    let blueprints = Day19::parse(input).expect("Failed to parse input");
+
+    Day19::part_two(&blueprints)
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Vec<Blueprint>;
+    type PartOne = u32;
+    type PartTwo = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        // Blueprints may be wrapped over several lines
+        let mut starts: Vec<_> = input.match_indices("Blueprint").map(|(i, _)| i).collect();
+        if let Some(garbage) = input[..starts.first().copied().unwrap_or(input.len())]
+            .split_whitespace()
+            .next()
+        {
+            return Err(Error::parse(input, garbage, "Expected `Blueprint`"));
+        }
+        starts.push(input.len());
+
+        starts
+            .windows(2)
+            .map(|w| {
+                let blueprint = input[w[0]..w[1]].trim();
+
+                blueprint
+                    .parse()
+                    .map_err(|e: Error| e.offset(Location::of(input, blueprint), blueprint))
+            })
+            .collect()
+    }
+
+    fn part_one(blueprints: &Self::Input) -> Self::PartOne {
+        max_geodes(blueprints, 24)
+            .into_iter()
+            .zip(blueprints)
+            .map(|(geodes, blueprint)| blueprint.id * geodes)
+            .sum()
+    }
+
+    fn part_two(blueprints: &Self::Input) -> Self::PartTwo {
+        let first = &blueprints[..blueprints.len().min(3)];
+
+        max_geodes(first, 32).into_iter().product()
+    }
+}
+
+/// The most geodes each blueprint can open in `minutes`, searching each on its own thread.
+fn max_geodes(blueprints: &[Blueprint], minutes: u32) -> Vec<u32> {
+    thread::scope(|s| {
+        let handles: Vec<_> = blueprints
+            .iter()
+            .map(|blueprint| s.spawn(move || blueprint.max_geodes(minutes)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("Blueprint search panicked"))
+            .collect()
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blueprint {
+    id: u32,
+    /// What a robot collecting each mineral costs, indexed by mineral.
+    costs: [[u32; 4]; 4],
+    /// The most of each mineral any robot costs. Only this much can be spent each minute, so
+    /// there's no point in having more robots collecting it.
+    max_spend: [u32; 4],
+}
+
+#[derive(Debug, Clone, Copy)]
+struct State {
+    minutes_left: u32,
+    robots: [u32; 4],
+    minerals: [u32; 4],
+}
+
+impl Blueprint {
+    fn new(id: u32, costs: [[u32; 4]; 4]) -> Self {
+        let mut max_spend = [0; 4];
+        for cost in &costs {
+            for (max, &c) in max_spend.iter_mut().zip(cost) {
+                *max = (*max).max(c);
+            }
+        }
+
+        Self {
+            id,
+            costs,
+            max_spend,
+        }
+    }
+
+    fn max_geodes(&self, minutes: u32) -> u32 {
+        let mut best = 0;
+        let mut robots = [0; 4];
+        robots[ORE] = 1;
+        let start = State {
+            minutes_left: minutes,
+            robots,
+            minerals: [0; 4],
+        };
+        self.search(start, &mut best);
+
+        best
+    }
+
+    /// Depth first search, deciding which robot to build next rather than what to do each
+    /// minute.
+    fn search(&self, state: State, best: &mut u32) {
+        let t = state.minutes_left;
+        let idle = state.minerals[GEODE] + state.robots[GEODE] * t;
+        *best = (*best).max(idle);
+
+        // Even building a geode robot every remaining minute can't beat the best so far
+        if idle + t * t.saturating_sub(1) / 2 <= *best {
+            return;
+        }
+
+        for robot in (0..4).rev() {
+            if robot != GEODE && state.robots[robot] >= self.max_spend[robot] {
+                continue;
+            }
+
+            // Skip ahead to when the robot is built, a robot finished in the last minute is
+            // useless
+            let Some(elapsed) = self.wait_for(robot, &state).map(|wait| wait + 1) else {
+                continue;
+            };
+            if elapsed >= t {
+                continue;
+            }
+
+            let mut next = state;
+            next.minutes_left -= elapsed;
+            for mineral in 0..4 {
+                next.minerals[mineral] = state.minerals[mineral] + state.robots[mineral] * elapsed
+                    - self.costs[robot][mineral];
+            }
+            next.robots[robot] += 1;
+
+            self.search(next, best);
+        }
+    }
+
+    /// Minutes until `robot` can be afforded, [`None`] if the current robots never collect
+    /// enough.
+    fn wait_for(&self, robot: usize, state: &State) -> Option<u32> {
+        (0..4).try_fold(0, |wait, mineral| {
+            let missing = self.costs[robot][mineral].saturating_sub(state.minerals[mineral]);
+
+            match (missing, state.robots[mineral]) {
+                (0, _) => Some(wait),
+                (_, 0) => None,
+                (missing, robots) => Some(wait.max(missing.div_ceil(robots))),
+            }
+        })
+    }
+}
+
+impl FromStr for Blueprint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("Blueprint")
+            .ok_or_else(|| Error::parse(s, s, "Expected `Blueprint`"))?;
+        let (id, rest) = rest
+            .split_once(':')
+            .ok_or_else(|| Error::parse(s, rest, "Expected `:` after the blueprint id"))?;
+        let id = parse_number(s, id.trim())?;
+
+        let mut costs = [[0; 4]; 4];
+        let mut defined = [false; 4];
+
+        for sentence in rest.split('.').map(str::trim).filter(|s| !s.is_empty()) {
+            let words: Vec<_> = sentence.split_whitespace().collect();
+            let (robot, cost) = match words[..] {
+                ["Each", robot, "robot", "costs", ref cost @ ..] => (robot, cost),
+                _ => {
+                    return Err(Error::parse(
+                        s,
+                        sentence,
+                        "Expected `Each <mineral> robot costs ...`",
+                    ))
+                }
+            };
+            let robot = mineral(s, robot)?;
+
+            for part in cost.split(|&w| w == "and") {
+                let [amount, name] = part[..] else {
+                    return Err(Error::parse(s, sentence, "Expected costs like `3 ore`"));
+                };
+
+                costs[robot][mineral(s, name)?] = parse_number(s, amount)?;
+            }
+            defined[robot] = true;
+        }
+
+        if let Some(missing) = defined.iter().position(|&d| !d) {
+            return Err(Error::parse(
+                s,
+                s,
+                format!("Missing the cost of the {} robot", MINERALS[missing]),
+            ));
+        }
+
+        Ok(Self::new(id, costs))
+    }
+}
+
+/// The index of the mineral called `name`, a sub slice of `s`.
+fn mineral(s: &str, name: &str) -> Result<usize, Error> {
+    MINERALS
+        .iter()
+        .position(|&m| m == name)
+        .ok_or_else(|| Error::parse(s, name, format!("Unknown mineral `{name}`")))
+}
+
+fn parse_number(s: &str, part: &str) -> Result<u32, Error> {
+    part.parse()
+        .map_err(|e| Error::parse(s, part, format!("Invalid number: {e}")))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day19, GEODE};
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = r#"
+Blueprint 1:
+  Each ore robot costs 4 ore.
+  Each clay robot costs 2 ore.
+  Each obsidian robot costs 3 ore and 14 clay.
+  Each geode robot costs 2 ore and 7 obsidian.
+
+Blueprint 2:
+  Each ore robot costs 2 ore.
+  Each clay robot costs 3 ore.
+  Each obsidian robot costs 3 ore and 8 clay.
+  Each geode robot costs 3 ore and 12 obsidian.
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 33)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 56 * 62)
+    }
+
+    #[test]
+    fn test_parse() {
+        let blueprints = Day19::parse(INPUT).unwrap();
+        let single_line = "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. \
+            Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.";
+
+        assert_eq!(blueprints.len(), 2);
+        assert_eq!(blueprints[1].costs[GEODE], [3, 0, 12, 0]);
+        assert_eq!(blueprints[1].max_spend, [3, 8, 12, 0]);
+        assert_eq!(Day19::parse(single_line).unwrap()[0], blueprints[0]);
+    }
+
+    #[test]
+    fn test_max_geodes() {
+        let blueprints = Day19::parse(INPUT).unwrap();
+
+        assert_eq!(blueprints[0].max_geodes(24), 9);
+        assert_eq!(blueprints[1].max_geodes(24), 12);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT.replace("7 obsidian", "7 diamond");
+        let Err(Error::Parse(e)) = Day19::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(6, 38));
+        assert_eq!(e.text, "diamond");
+
+        let input = INPUT.replace("  Each clay robot costs 3 ore.\n", "");
+        assert!(Day19::parse(&input).is_err());
+        assert!(Day19::parse("Recipe 1:").is_err());
     }
 }
@@ -1,22 +1,137 @@
+use crate::{circular::CircularList, try_parse_lines, Error, Solution};
+
+const DECRYPTION_KEY: i64 = 811_589_153;
+
 pub fn star_one(input: &str) -> i64 {
-    0
+    let numbers = Day20::parse(input).expect("Failed to parse input");
+
+    Day20::part_one(&numbers)
 }
 
 pub fn star_two(input: &str) -> i64 {
-    0
+    let numbers = Day20::parse(input).expect("Failed to parse input");
+
+    Day20::part_two(&numbers)
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<i64>;
+    type PartOne = i64;
+    type PartTwo = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let numbers: Vec<i64> = try_parse_lines(input).collect::<Result<_, _>>()?;
+
+        if !numbers.contains(&0) {
+            return Err("The encrypted file should contain a 0".into());
+        }
+
+        Ok(numbers)
+    }
+
+    fn part_one(numbers: &Self::Input) -> Self::PartOne {
+        mix(numbers, 1, 1)
+            .and_then(|list| grove_coordinates(&list))
+            .expect("Failed to find the grove coordinates")
+    }
+
+    fn part_two(numbers: &Self::Input) -> Self::PartTwo {
+        mix(numbers, DECRYPTION_KEY, 10)
+            .and_then(|list| grove_coordinates(&list))
+            .expect("Failed to find the grove coordinates")
+    }
+}
+
+/// Multiply every number by `key` and mix them `rounds` times.
+///
+/// Numbers move in their original order, duplicates are told apart by their original index.
+/// Fails if a number multiplied by `key` doesn't fit in 64 bits.
+fn mix(numbers: &[i64], key: i64, rounds: usize) -> Result<CircularList<i64>, Error> {
+    let numbers = numbers
+        .iter()
+        .map(|&n| {
+            n.checked_mul(key)
+                .ok_or_else(|| Error::from(format!("{n} * {key} doesn't fit in 64 bits")))
+        })
+        .collect::<Result<_, _>>()?;
+    let mut list = CircularList::new(numbers);
+
+    for _ in 0..rounds {
+        for handle in 0..list.len() {
+            let offset = *list.get(handle);
+            list.move_by(handle, offset);
+        }
+    }
+
+    Ok(list)
+}
+
+/// The sum of the numbers 1000, 2000 and 3000 places after the 0, failing if it doesn't fit in
+/// 64 bits.
+fn grove_coordinates(list: &CircularList<i64>) -> Result<i64, Error> {
+    let zero = (0..list.len())
+        .find(|&handle| *list.get(handle) == 0)
+        .expect("The encrypted file should contain a 0");
+    let start = list.position(zero);
+
+    [1000, 2000, 3000]
+        .iter()
+        .map(|offset| *list.get(list.handle_at(start + offset)))
+        .try_fold(0_i64, |sum, n| {
+            sum.checked_add(n)
+                .ok_or_else(|| "The grove coordinates don't fit in 64 bits".into())
+        })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{mix, star_one, star_two, Day20, DECRYPTION_KEY};
+    use crate::{Error, Solution};
+
+    const INPUT: &str = r#"
+1
+2
+-3
+3
+-2
+0
+4
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 3)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 1623178306)
+    }
+
+    #[test]
+    fn test_mix() {
+        let numbers = Day20::parse(INPUT).unwrap();
+        let mixed: Vec<_> = mix(&numbers, 1, 1).unwrap().iter().copied().collect();
+
+        // Same as `1, 2, -3, 4, 0, 3, -2` rotated
+        assert_eq!(mixed, vec![-2, 1, 2, -3, 4, 0, 3]);
+    }
+
+    #[test]
+    fn test_key_overflow() {
+        let numbers = Day20::parse("0\n12000000000\n-5").unwrap();
+
+        assert!(mix(&numbers, 1, 1).is_ok());
+        assert!(matches!(
+            mix(&numbers, DECRYPTION_KEY, 10),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_without_zero() {
+        assert!(matches!(Day20::parse("1\n2\n3"), Err(Error::Invalid(_))));
     }
 }
@@ -1,22 +1,384 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::{try_parse_lines, Error, Solution};
+
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
 pub fn star_one(input: &str) -> i64 {
-    0
+    let riddle = Day21::parse(input).expect("Failed to parse input");
+
+    Day21::part_one(&riddle)
 }
 
 pub fn star_two(input: &str) -> i64 {
-    0
+    let riddle = Day21::parse(input).expect("Failed to parse input");
+
+    Day21::part_two(&riddle)
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = Riddle;
+    type PartOne = i64;
+    type PartTwo = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let jobs: Vec<NamedJob> = try_parse_lines(input).collect::<Result<_, _>>()?;
+
+        Riddle::new(jobs)
+    }
+
+    fn part_one(riddle: &Self::Input) -> Self::PartOne {
+        let values = riddle.evaluate().expect("Monkeys shouldn't divide by zero");
+
+        to_whole(&values[riddle.root])
+    }
+
+    fn part_two(riddle: &Self::Input) -> Self::PartTwo {
+        to_whole(
+            &riddle
+                .solve_for_human()
+                .expect("Expected exactly one solution for humn"),
+        )
+    }
+}
+
+fn to_whole(value: &BigRational) -> i64 {
+    assert!(value.is_integer(), "Expected a whole number, got {value}");
+
+    value
+        .to_integer()
+        .to_i64()
+        .unwrap_or_else(|| panic!("{value} doesn't fit in 64 bits"))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Op {
+    fn apply(self, lhs: &BigRational, rhs: &BigRational) -> Option<BigRational> {
+        match self {
+            Op::Add => Some(lhs + rhs),
+            Op::Sub => Some(lhs - rhs),
+            Op::Mul => Some(lhs * rhs),
+            Op::Div => (!rhs.is_zero()).then(|| lhs / rhs),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Job {
+    Number(i64),
+    /// Operands are indices of other monkeys.
+    Operation(usize, Op, usize),
+}
+
+/// Every monkey's job, monkeys may share operands, but there are no cycles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Riddle {
+    jobs: Vec<Job>,
+    /// Every monkey comes after the monkeys it listens to.
+    order: Vec<usize>,
+    root: usize,
+    human: usize,
+}
+
+impl Riddle {
+    fn new(named: Vec<NamedJob>) -> Result<Self, Error> {
+        let mut indices = HashMap::new();
+        for (idx, job) in named.iter().enumerate() {
+            if indices.insert(job.name.as_str(), idx).is_some() {
+                return Err(format!("Monkey {} has more than one job", job.name).into());
+            }
+        }
+
+        let index = |name: &str| {
+            indices
+                .get(name)
+                .copied()
+                .ok_or_else(|| Error::from(format!("Missing a job for monkey {name}")))
+        };
+        let jobs = named
+            .iter()
+            .map(|job| match &job.job {
+                RawJob::Number(n) => Ok(Job::Number(*n)),
+                RawJob::Operation(lhs, op, rhs) => {
+                    Ok(Job::Operation(index(lhs)?, *op, index(rhs)?))
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let (root, human) = (index(ROOT)?, index(HUMAN)?);
+
+        let order = topological_order(&jobs)
+            .ok_or_else(|| Error::from("Monkeys wait for each other in a cycle"))?;
+
+        Ok(Self {
+            jobs,
+            order,
+            root,
+            human,
+        })
+    }
+
+    /// Every monkey's number, [`None`] if one divides by zero.
+    fn evaluate(&self) -> Option<Vec<BigRational>> {
+        self.evaluate_where(|_| true)
+    }
+
+    /// The numbers of the monkeys `include` accepts, zero for the others, [`None`] if one
+    /// divides by zero. The monkeys an included monkey listens to must be included too.
+    fn evaluate_where<F>(&self, mut include: F) -> Option<Vec<BigRational>>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let mut values = vec![BigRational::zero(); self.jobs.len()];
+
+        for &idx in self.order.iter().filter(|&&idx| include(idx)) {
+            values[idx] = match self.jobs[idx] {
+                Job::Number(n) => BigRational::from_integer(BigInt::from(n)),
+                Job::Operation(lhs, op, rhs) => op.apply(&values[lhs], &values[rhs])?,
+            };
+        }
+
+        Some(values)
+    }
+
+    /// The number to yell so both of root's operands are equal.
+    ///
+    /// Starting at root the operation chain leading to `humn` is inverted one step at a time,
+    /// [`None`] if that's not possible because both operands of an operation depend on `humn`,
+    /// or there's no unique solution. Only the monkeys that don't depend on `humn` are
+    /// evaluated, so the number listed for `humn` is never used.
+    fn solve_for_human(&self) -> Option<BigRational> {
+        let mut depends = vec![false; self.jobs.len()];
+        depends[self.human] = true;
+        for &idx in &self.order {
+            if let Job::Operation(lhs, _, rhs) = self.jobs[idx] {
+                depends[idx] = depends[lhs] || depends[rhs];
+            }
+        }
+        let values = self.evaluate_where(|idx| !depends[idx])?;
+
+        let Job::Operation(lhs, _, rhs) = self.jobs[self.root] else {
+            return None;
+        };
+        let (mut current, mut target) = match (depends[lhs], depends[rhs]) {
+            (true, false) => (lhs, values[rhs].clone()),
+            (false, true) => (rhs, values[lhs].clone()),
+            _ => return None,
+        };
+
+        while current != self.human {
+            let Job::Operation(lhs, op, rhs) = self.jobs[current] else {
+                return None;
+            };
+
+            (current, target) = match (depends[lhs], depends[rhs], op) {
+                (true, true, _) | (false, false, _) => return None,
+                // target = x + rhs, target = x - rhs, ...
+                (true, false, Op::Add) => (lhs, target - &values[rhs]),
+                (true, false, Op::Sub) => (lhs, target + &values[rhs]),
+                (true, false, Op::Mul) => (lhs, Op::Div.apply(&target, &values[rhs])?),
+                // x / 0 has no value, so it can't be made equal to anything
+                (true, false, Op::Div) if values[rhs].is_zero() => return None,
+                (true, false, Op::Div) => (lhs, target * &values[rhs]),
+                // target = lhs + x, target = lhs - x, ...
+                (false, true, Op::Add) => (rhs, target - &values[lhs]),
+                (false, true, Op::Sub) => (rhs, &values[lhs] - target),
+                (false, true, Op::Mul) => (rhs, Op::Div.apply(&target, &values[lhs])?),
+                (false, true, Op::Div) => (rhs, Op::Div.apply(&values[lhs], &target)?),
+            };
+        }
+
+        Some(target)
+    }
+}
+
+/// Depth first topological sort, [`None`] if there's a cycle.
+fn topological_order(jobs: &[Job]) -> Option<Vec<usize>> {
+    #[derive(Copy, Clone, PartialEq, Eq)]
+    enum Mark {
+        New,
+        Visiting,
+        Done,
+    }
+
+    let mut marks = vec![Mark::New; jobs.len()];
+    let mut order = Vec::with_capacity(jobs.len());
+
+    for start in 0..jobs.len() {
+        // Each monkey is pushed once to be expanded and once more to be finished
+        let mut stack = vec![(start, false)];
+
+        while let Some((idx, finished)) = stack.pop() {
+            if finished {
+                marks[idx] = Mark::Done;
+                order.push(idx);
+                continue;
+            }
+
+            match marks[idx] {
+                Mark::Done => continue,
+                Mark::Visiting => return None,
+                Mark::New => {}
+            }
+            marks[idx] = Mark::Visiting;
+            stack.push((idx, true));
+
+            if let Job::Operation(lhs, _, rhs) = jobs[idx] {
+                for operand in [lhs, rhs] {
+                    match marks[operand] {
+                        Mark::Visiting => return None,
+                        Mark::New => stack.push((operand, false)),
+                        Mark::Done => {}
+                    }
+                }
+            }
+        }
+    }
+
+    Some(order)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RawJob {
+    Number(i64),
+    Operation(String, Op, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NamedJob {
+    name: String,
+    job: RawJob,
+}
+
+impl FromStr for NamedJob {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, job) = s
+            .split_once(':')
+            .ok_or_else(|| Error::parse(s, s, "Expected `<name>: <job>`"))?;
+        let job = job.trim();
+
+        let job = match job.split_whitespace().collect::<Vec<_>>()[..] {
+            [number] => RawJob::Number(
+                number
+                    .parse()
+                    .map_err(|e| Error::parse(s, number, format!("Invalid number: {e}")))?,
+            ),
+            [lhs, op, rhs] => {
+                let op = match op {
+                    "+" => Op::Add,
+                    "-" => Op::Sub,
+                    "*" => Op::Mul,
+                    "/" => Op::Div,
+                    _ => return Err(Error::parse(s, op, format!("Unknown operator `{op}`"))),
+                };
+
+                RawJob::Operation(lhs.to_owned(), op, rhs.to_owned())
+            }
+            _ => {
+                return Err(Error::parse(
+                    s,
+                    job,
+                    "Expected a number or an operation like `abcd + efgh`",
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: name.trim().to_owned(),
+            job,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day21};
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = r#"
+root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 152)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 301)
+    }
+
+    #[test]
+    fn test_exact_division() {
+        // Solving goes through `humn / 4 = 3/2`, which integer division would get wrong
+        let input = "root: six + nine\nsix: frac * half\nfrac: humn / four\nhalf: 6\nfour: 4\nnine: 9\nhumn: 1";
+
+        assert_eq!(star_two(input), 6);
+
+        // humn on the right hand side of a division
+        let input = "root: div + two\ndiv: ten / humn\nten: 10\ntwo: 2\nhumn: 1";
+        assert_eq!(star_two(input), 5);
+    }
+
+    #[test]
+    fn test_human_value_unused() {
+        // Evaluating with the listed number would divide by zero
+        let input = "root: ten + x\nx: ten / humn\nten: 10\nhumn: 0";
+
+        assert_eq!(star_two(input), 1);
+    }
+
+    #[test]
+    fn test_human_divided_by_zero() {
+        let input = "root: ten + x\nx: humn / zero\nten: 10\nzero: 0\nhumn: 5";
+        let riddle = Day21::parse(input).unwrap();
+
+        assert_eq!(riddle.solve_for_human(), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT.replace("hmdt - zczc", "hmdt % zczc");
+        let Err(Error::Parse(e)) = Day21::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(15, 12));
+
+        let input = INPUT.replace("hmdt: 32", "hmdt: drzm + zczc");
+        assert!(matches!(Day21::parse(&input), Err(Error::Invalid(_))));
+
+        let input = INPUT.replace("humn: 5", "human: 5");
+        assert!(matches!(Day21::parse(&input), Err(Error::Invalid(_))));
     }
 }
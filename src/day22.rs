@@ -1,22 +1,549 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    math::{Vector2, Vector3},
+    Error, Solution,
+};
+
+pub fn star_one(input: &str) -> isize {
+    let notes = Day22::parse(input).expect("Failed to parse input");
+
+    Day22::part_one(&notes)
+}
+
+pub fn star_two(input: &str) -> isize {
+    let notes = Day22::parse(input).expect("Failed to parse input");
+
+    Day22::part_two(&notes)
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    /// The board and the path to follow on it.
+    type Input = (Board, Vec<Instruction>);
+    type PartOne = isize;
+    type PartTwo = isize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        // Leading spaces are part of the board, so only skip empty lines
+        let notes = input.trim_start_matches(['\n', '\r']);
+        let (board, path) = split_at_blank_line(notes).ok_or_else(|| {
+            Error::from("Expected the board and the path separated by an empty line")
+        })?;
+
+        Ok((parse_board(input, board)?, parse_path(input, path.trim())?))
+    }
+
+    fn part_one((board, path): &Self::Input) -> Self::PartOne {
+        walk(board, path, |position, facing| {
+            board.wrap_flat(position, facing)
+        })
+    }
+
+    fn part_two((board, path): &Self::Input) -> Self::PartTwo {
+        let cube = Cube::fold(board).expect("The board should be the net of a cube");
+
+        walk(board, path, |position, facing| cube.wrap(position, facing))
+    }
+}
+
+/// Follow `path` from the start, using `wrap` to step off the edge of the board, returning the
+/// final password.
+fn walk<F>(board: &Board, path: &[Instruction], wrap: F) -> isize
+where
+    F: Fn(Vector2<isize>, Facing) -> (Vector2<isize>, Facing),
+{
+    let mut position = board.start();
+    let mut facing = Facing::RIGHT;
+
+    for instruction in path {
+        match instruction {
+            Instruction::Left => facing = facing.turn_left(),
+            Instruction::Right => facing = facing.turn_right(),
+            Instruction::Forward(steps) => {
+                for _ in 0..*steps {
+                    let mut next = (position + facing.delta(), facing);
+                    if board.get(next.0) == Tile::Void {
+                        next = wrap(position, facing);
+                    }
+
+                    if board.get(next.0) == Tile::Wall {
+                        break;
+                    }
+                    (position, facing) = next;
+                }
+            }
+        }
+    }
+
+    1000 * (position.y + 1) + 4 * (position.x + 1) + facing.value()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Tile {
+    /// Not part of the board.
+    Void,
+    Open,
+    Wall,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    /// Rows padded with [`Tile::Void`] to the same width.
+    tiles: Vec<Vec<Tile>>,
+}
+
+impl Board {
+    fn width(&self) -> usize {
+        self.tiles.first().map_or(0, Vec::len)
+    }
+
+    fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    fn get(&self, position: Vector2<isize>) -> Tile {
+        if position.x < 0 || position.y < 0 {
+            return Tile::Void;
+        }
+
+        self.tiles
+            .get(position.y as usize)
+            .and_then(|row| row.get(position.x as usize))
+            .copied()
+            .unwrap_or(Tile::Void)
+    }
+
+    /// The leftmost open tile of the top row.
+    fn start(&self) -> Vector2<isize> {
+        let x = self.tiles[0]
+            .iter()
+            .position(|&t| t == Tile::Open)
+            .expect("The top row should have an open tile");
+
+        Vector2::new(x as isize, 0)
+    }
+
+    /// Where walking off the board leads when wrapping around to the other side of the row or
+    /// column.
+    fn wrap_flat(&self, position: Vector2<isize>, facing: Facing) -> (Vector2<isize>, Facing) {
+        let back = -facing.delta();
+        let mut wrapped = position;
+        while self.get(wrapped + back) != Tile::Void {
+            wrapped += back;
+        }
+
+        (wrapped, facing)
+    }
+}
+
+/// Orientation of a face of the folded cube, all vectors are unit vectors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Frame {
+    /// Points out of the cube.
+    normal: Vector3<isize>,
+    /// Direction of walking right on the face.
+    right: Vector3<isize>,
+    /// Direction of walking down on the face.
+    down: Vector3<isize>,
+}
+
+impl Frame {
+    fn direction(&self, facing: Facing) -> Vector3<isize> {
+        let delta = facing.delta();
+
+        self.right * delta.x + self.down * delta.y
+    }
+
+    /// The frame of the face next to this one in the net, folded over the edge in `facing`.
+    fn fold(&self, facing: Facing) -> Self {
+        let delta = facing.delta();
+
+        Self {
+            normal: self.direction(facing),
+            right: if delta.x == 0 {
+                self.right
+            } else {
+                -self.normal * delta.x
+            },
+            down: if delta.y == 0 {
+                self.down
+            } else {
+                -self.normal * delta.y
+            },
+        }
+    }
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+/// The board folded into a cube.
+///
+/// Every face of the net is given its orientation in 3D by folding outwards from the first
+/// face. Walking off a face in some direction leads onto the face whose normal points that way,
+/// which works for any net rather than just a particular layout.
+#[derive(Debug, Clone)]
+struct Cube {
+    size: isize,
+    /// Faces by their position in the net, in units of faces.
+    faces: HashMap<Vector2<isize>, Frame>,
+    by_normal: HashMap<Vector3<isize>, Vector2<isize>>,
+}
+
+impl Cube {
+    fn fold(board: &Board) -> Result<Self, Error> {
+        let area = board
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|&&t| t != Tile::Void)
+            .count();
+        let size = ((area / 6) as f64).sqrt() as usize;
+        if size == 0 || 6 * size * size != area {
+            return Err(format!("A board with {area} tiles can't be folded into a cube").into());
+        }
+
+        let in_net = |face: Vector2<isize>| board.get(face * size as isize) != Tile::Void;
+        let first = (0..board.width().div_ceil(size))
+            .map(|x| Vector2::new(x as isize, 0))
+            .find(|&face| in_net(face))
+            .ok_or_else(|| Error::from("The board is empty"))?;
+
+        let mut faces = HashMap::from([(
+            first,
+            Frame {
+                normal: Vector3::new(0, 0, 1),
+                right: Vector3::new(1, 0, 0),
+                down: Vector3::new(0, 1, 0),
+            },
+        )]);
+        let mut queue = VecDeque::from([first]);
+
+        while let Some(face) = queue.pop_front() {
+            let frame = faces[&face];
+
+            for facing in Facing::ALL {
+                let next = face + facing.delta();
+
+                if in_net(next) && !faces.contains_key(&next) {
+                    faces.insert(next, frame.fold(facing));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let by_normal: HashMap<_, _> = faces
+            .iter()
+            .map(|(&face, frame)| (frame.normal, face))
+            .collect();
+        if faces.len() != 6 || by_normal.len() != 6 {
+            return Err("The board isn't the net of a cube".into());
+        }
+
+        Ok(Self {
+            size: size as isize,
+            faces,
+            by_normal,
+        })
+    }
+
+    /// Where walking off the edge of a face leads, onto the face around the corner of the cube.
+    fn wrap(&self, position: Vector2<isize>, facing: Facing) -> (Vector2<isize>, Facing) {
+        let size = self.size;
+        let face = position / size;
+        let local = position - face * size;
+        let frame = self.faces[&face];
+
+        // The position along the edge being crossed, and which way it counts in 3D
+        let (along, tangent) = if facing.is_horizontal() {
+            (local.y, frame.down)
+        } else {
+            (local.x, frame.right)
+        };
+
+        let next_face = self.by_normal[&frame.direction(facing)];
+        let next_frame = self.faces[&next_face];
+        // Around the corner walking continues straight into the cube
+        let next_facing = Facing::ALL
+            .into_iter()
+            .find(|&f| next_frame.direction(f) == -frame.normal)
+            .expect("Adjacent faces are perpendicular");
+
+        let next_tangent = if next_facing.is_horizontal() {
+            next_frame.down
+        } else {
+            next_frame.right
+        };
+        let along = if next_tangent == tangent {
+            along
+        } else {
+            size - 1 - along
+        };
+        // Enter on the edge the new facing points away from
+        let entry = |step: isize| if step < 0 { size - 1 } else { 0 };
+        let delta = next_facing.delta();
+        let local = if next_facing.is_horizontal() {
+            Vector2::new(entry(delta.x), along)
+        } else {
+            Vector2::new(along, entry(delta.y))
+        };
+
+        (next_face * size + local, next_facing)
+    }
+}
+
+/// Which way you're facing, as a single step on the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Facing(Vector2<isize>);
+
+impl Facing {
+    const RIGHT: Facing = Facing(Vector2 { x: 1, y: 0 });
+    const DOWN: Facing = Facing(Vector2 { x: 0, y: 1 });
+    const LEFT: Facing = Facing(Vector2 { x: -1, y: 0 });
+    const UP: Facing = Facing(Vector2 { x: 0, y: -1 });
+
+    /// Ordered by their value in the password.
+    const ALL: [Facing; 4] = [Facing::RIGHT, Facing::DOWN, Facing::LEFT, Facing::UP];
+
+    fn turn_right(self) -> Self {
+        Self(self.0.rotate_right())
+    }
+
+    fn turn_left(self) -> Self {
+        Self(self.0.rotate_left())
+    }
+
+    fn delta(self) -> Vector2<isize> {
+        self.0
+    }
+
+    fn is_horizontal(self) -> bool {
+        self.0.y == 0
+    }
+
+    /// The value of the facing in the password.
+    fn value(self) -> isize {
+        Self::ALL
+            .iter()
+            .position(|&f| f == self)
+            .expect("Facings are always unit steps along an axis") as isize
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Forward(usize),
+    Left,
+    Right,
+}
+
+/// Split `s` around its first blank line, with either line ending.
+fn split_at_blank_line(s: &str) -> Option<(&str, &str)> {
+    let mut offset = 0;
+
+    for line in s.split_inclusive('\n') {
+        if line.trim_end_matches(['\n', '\r']).is_empty() {
+            return Some((&s[..offset], &s[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Parse the `board` part of `input`.
+fn parse_board(input: &str, board: &str) -> Result<Board, Error> {
+    let mut tiles = board
+        .lines()
+        .map(|line| {
+            line.char_indices()
+                .map(|(idx, c)| match c {
+                    ' ' => Ok(Tile::Void),
+                    '.' => Ok(Tile::Open),
+                    '#' => Ok(Tile::Wall),
+                    _ => Err(Error::parse(
+                        input,
+                        &line[idx..idx + c.len_utf8()],
+                        format!("Unknown tile `{c}`"),
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let width = tiles.iter().map(Vec::len).max().unwrap_or_default();
+    for row in &mut tiles {
+        row.resize(width, Tile::Void);
+    }
+
+    if !tiles.first().is_some_and(|row| row.contains(&Tile::Open)) {
+        return Err("The top row of the board should have an open tile".into());
+    }
+
+    Ok(Board { tiles })
+}
+
+/// Parse the `path` part of `input`, like `10R5L5`.
+fn parse_path(input: &str, path: &str) -> Result<Vec<Instruction>, Error> {
+    let mut instructions = vec![];
+    let mut rest = path;
+
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            'L' | 'R' => {
+                instructions.push(if c == 'L' {
+                    Instruction::Left
+                } else {
+                    Instruction::Right
+                });
+
+                1
+            }
+            '0'..='9' => {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let steps = rest[..len].parse().map_err(|e| {
+                    Error::parse(input, &rest[..len], format!("Invalid number of steps: {e}"))
+                })?;
+                instructions.push(Instruction::Forward(steps));
+
+                len
+            }
+            _ => {
+                return Err(Error::parse(
+                    input,
+                    &rest[..c.len_utf8()],
+                    format!("Unknown instruction `{c}`"),
+                ))
+            }
+        };
+
+        rest = &rest[len..];
+    }
+
+    Ok(instructions)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{parse_board, star_one, star_two, Cube, Day22, Facing, Tile};
+    use crate::{math::Vector2, Error, Location, Solution};
+
+    const INPUT: &str = r#"
+        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 6032)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 5031)
+    }
+
+    #[test]
+    fn test_crlf() {
+        let input = INPUT.replace('\n', "\r\n");
+
+        assert_eq!(star_one(&input), 6032);
+        assert_eq!(star_two(&input), 5031);
+    }
+
+    /// A board of open tiles with a face of the given size wherever the layout has a `#`.
+    fn net(layout: &str, size: usize) -> String {
+        layout
+            .lines()
+            .flat_map(|row| {
+                let line: String = row
+                    .chars()
+                    .map(|c| {
+                        if c == '#' {
+                            ".".repeat(size)
+                        } else {
+                            " ".repeat(size)
+                        }
+                    })
+                    .collect();
+
+                vec![line; size]
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_fold_round_trip() {
+        let example = Day22::parse(INPUT).unwrap().0;
+        let layouts = [".#..\n####\n.#..", ".##\n.#.\n##.\n#..", "#...\n###.\n..##"];
+        let boards = std::iter::once(example)
+            .chain(layouts.iter().map(|l| parse_board(l, &net(l, 3)).unwrap()));
+
+        for board in boards {
+            let cube = Cube::fold(&board).unwrap();
+
+            for y in 0..board.height() as isize {
+                for x in 0..board.width() as isize {
+                    let position = Vector2::new(x, y);
+                    if board.get(position) == Tile::Void {
+                        continue;
+                    }
+
+                    for facing in Facing::ALL {
+                        if board.get(position + facing.delta()) != Tile::Void {
+                            continue;
+                        }
+
+                        // Walking off an edge and straight back leads to where you started
+                        let (wrapped, wrapped_facing) = cube.wrap(position, facing);
+                        let back = wrapped_facing.turn_left().turn_left();
+
+                        assert_ne!(board.get(wrapped), Tile::Void);
+                        assert_eq!(
+                            cube.wrap(wrapped, back),
+                            (position, facing.turn_left().turn_left()),
+                            "Wrapping {position:?} facing {facing:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_invalid() {
+        let layout = "####\n##..";
+        let board = parse_board(layout, &net(layout, 2)).unwrap();
+
+        assert!(Cube::fold(&board).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT.replace("10R5L5R10L4R5L5", "10R5X5");
+        let Err(Error::Parse(e)) = Day22::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(15, 5));
+
+        let input = INPUT.replace(".#..\n        #...", ".#..\n        #..x");
+        let Err(Error::Parse(e)) = Day22::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(4, 12));
     }
 }
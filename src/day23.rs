@@ -1,22 +1,293 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use crate::{math::Vector2, Error, Solution};
+
+/// Elves consider moving north, south, west, then east, each checking the three cells on that
+/// side. The first direction is moved to the back after every round.
+const DIRECTIONS: [[Vector2<isize>; 3]; 4] = [
+    [v(0, -1), v(-1, -1), v(1, -1)],
+    [v(0, 1), v(-1, 1), v(1, 1)],
+    [v(-1, 0), v(-1, -1), v(-1, 1)],
+    [v(1, 0), v(1, -1), v(1, 1)],
+];
+
+const fn v(x: isize, y: isize) -> Vector2<isize> {
+    Vector2 { x, y }
+}
+
+pub fn star_one(input: &str) -> usize {
+    let elves = Day23::parse(input).expect("Failed to parse input");
+
+    Day23::part_one(&elves)
+}
+
+pub fn star_two(input: &str) -> usize {
+    let elves = Day23::parse(input).expect("Failed to parse input");
+
+    Day23::part_two(&elves)
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Vec<Vector2<isize>>;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        let mut elves = vec![];
+
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.trim_end().char_indices() {
+                match c {
+                    '#' => elves.push(Vector2::new(x as isize, y as isize)),
+                    '.' => {}
+                    _ => {
+                        return Err(Error::parse(
+                            input,
+                            &line[x..x + c.len_utf8()],
+                            format!("Unknown tile `{c}`"),
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(elves)
+    }
+
+    fn part_one(elves: &Self::Input) -> Self::PartOne {
+        let mut diffusion = Diffusion::new(elves.clone());
+        for _ in 0..10 {
+            diffusion.round();
+        }
+
+        diffusion.empty_ground()
+    }
+
+    fn part_two(elves: &Self::Input) -> Self::PartTwo {
+        let mut diffusion = Diffusion::new(elves.clone());
+        let mut rounds = 1;
+        while diffusion.round() {
+            rounds += 1;
+        }
+
+        rounds
+    }
+}
+
+/// A dense grid of which cells have an elf, covering a rectangle that's grown as elves spread.
+#[derive(Debug, Clone)]
+struct Field {
+    /// The position of the top left cell.
+    origin: Vector2<isize>,
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+}
+
+impl Field {
+    /// A field covering every elf with `margin` empty cells on each side.
+    fn covering(elves: &[Vector2<isize>], margin: isize) -> Self {
+        let (min, max) = bounds(elves);
+        let origin = min - Vector2::new(margin, margin);
+        let size = max - min + Vector2::new(2 * margin + 1, 2 * margin + 1);
+
+        let mut field = Self {
+            origin,
+            width: size.x as usize,
+            height: size.y as usize,
+            cells: vec![false; (size.x * size.y) as usize],
+        };
+        for &elf in elves {
+            let idx = field.index(elf);
+            field.cells[idx] = true;
+        }
+
+        field
+    }
+
+    /// Whether every position within `distance` of the elves is inside the field.
+    fn covers(&self, elves: &[Vector2<isize>], distance: isize) -> bool {
+        let (min, max) = bounds(elves);
+
+        min.x - distance >= self.origin.x
+            && min.y - distance >= self.origin.y
+            && max.x + distance < self.origin.x + self.width as isize
+            && max.y + distance < self.origin.y + self.height as isize
+    }
+
+    /// Index of `position`, which must be inside the field.
+    fn index(&self, position: Vector2<isize>) -> usize {
+        let local = position - self.origin;
+
+        local.y as usize * self.width + local.x as usize
+    }
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+/// The elves spreading out, one round at a time.
+#[derive(Debug, Clone)]
+struct Diffusion {
+    elves: Vec<Vector2<isize>>,
+    field: Field,
+    /// How many elves proposed moving to each cell of the field.
+    proposals: Vec<u8>,
+    round: usize,
+}
+
+impl Diffusion {
+    fn new(elves: Vec<Vector2<isize>>) -> Self {
+        let field = Field::covering(&elves, 1);
+
+        Self {
+            proposals: vec![0; field.cells.len()],
+            elves,
+            field,
+            round: 0,
+        }
+    }
+
+    /// Play a round, returning whether any elf moved.
+    fn round(&mut self) -> bool {
+        if self.elves.is_empty() {
+            return false;
+        }
+
+        // Elves look one cell around and move at most one cell, so that much space is needed
+        if !self.field.covers(&self.elves, 1) {
+            let (min, max) = bounds(&self.elves);
+            let margin = (max.x - min.x).max(max.y - min.y) / 2 + 8;
+
+            self.field = Field::covering(&self.elves, margin);
+            self.proposals = vec![0; self.field.cells.len()];
+        }
+
+        let targets: Vec<_> = self.elves.iter().map(|&elf| self.propose(elf)).collect();
+        for &target in targets.iter().flatten() {
+            self.proposals[self.field.index(target)] += 1;
+        }
+
+        let mut moved = false;
+        for (elf, target) in self.elves.iter_mut().zip(&targets) {
+            let Some(target) = *target else {
+                continue;
+            };
+            let target_idx = self.field.index(target);
+
+            if self.proposals[target_idx] == 1 {
+                let idx = self.field.index(*elf);
+                self.field.cells[idx] = false;
+                self.field.cells[target_idx] = true;
+                *elf = target;
+                moved = true;
+            }
+        }
+
+        for &target in targets.iter().flatten() {
+            self.proposals[self.field.index(target)] = 0;
+        }
+        self.round += 1;
+
+        moved
+    }
+
+    /// Where `elf` wants to move this round, [`None`] if it has no neighbours or nowhere to go.
+    fn propose(&self, elf: Vector2<isize>) -> Option<Vector2<isize>> {
+        let occupied = |position: Vector2<isize>| self.field.cells[self.field.index(position)];
+
+        if !elf.neighbours8().any(occupied) {
+            return None;
+        }
+
+        (0..4)
+            .map(|i| DIRECTIONS[(self.round + i) % 4])
+            .find(|cells| !cells.iter().any(|&offset| occupied(elf + offset)))
+            .map(|cells| elf + cells[0])
+    }
+
+    /// Empty cells in the smallest rectangle containing every elf.
+    fn empty_ground(&self) -> usize {
+        if self.elves.is_empty() {
+            return 0;
+        }
+        let (min, max) = bounds(&self.elves);
+        let area = (max.x - min.x + 1) * (max.y - min.y + 1);
+
+        area as usize - self.elves.len()
+    }
+}
+
+/// The smallest and largest coordinates of the elves, the origin if there are none.
+fn bounds(elves: &[Vector2<isize>]) -> (Vector2<isize>, Vector2<isize>) {
+    let Some(&first) = elves.first() else {
+        return (Vector2::default(), Vector2::default());
+    };
+
+    elves.iter().fold((first, first), |(min, max), elf| {
+        (
+            Vector2::new(min.x.min(elf.x), min.y.min(elf.y)),
+            Vector2::new(max.x.max(elf.x), max.y.max(elf.y)),
+        )
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day23, Diffusion};
+    use crate::{math::Vector2, Error, Location, Solution};
+
+    const INPUT: &str = r#"
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 110)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 20)
+    }
+
+    #[test]
+    fn test_small_example() {
+        let elves = Day23::parse(".....\n..##.\n..#..\n.....\n..##.\n.....").unwrap();
+        let mut diffusion = Diffusion::new(elves);
+
+        for _ in 0..3 {
+            assert!(diffusion.round());
+        }
+        assert!(!diffusion.round());
+
+        let mut elves = diffusion.elves.clone();
+        elves.sort_by_key(|e| (e.y, e.x));
+        let expected = [(2, 0), (4, 1), (0, 2), (4, 3), (2, 5)].map(|(x, y)| Vector2::new(x, y));
+        assert_eq!(elves, expected);
+    }
+
+    #[test]
+    fn test_field_grows() {
+        let mut diffusion = Diffusion::new(Day23::parse(INPUT).unwrap());
+        let initial = diffusion.field.cells.len();
+
+        while diffusion.round() {
+            assert!(diffusion.field.covers(&diffusion.elves, 0));
+        }
+        assert!(diffusion.field.cells.len() > initial);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let Err(Error::Parse(e)) = Day23::parse("..#\n.x.") else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 2));
+
+        assert_eq!(star_one(""), 0);
     }
 }
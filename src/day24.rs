@@ -1,22 +1,292 @@
-pub fn star_one(input: &str) -> i64 {
-    0
+use std::str::FromStr;
+
+use crate::{math::Vector2, search, Error, Solution};
+
+const MOVES: [Vector2<isize>; 5] = [
+    Vector2 { x: 0, y: 0 },
+    Vector2 { x: 1, y: 0 },
+    Vector2 { x: 0, y: 1 },
+    Vector2 { x: -1, y: 0 },
+    Vector2 { x: 0, y: -1 },
+];
+
+pub fn star_one(input: &str) -> usize {
+    let valley = Day24::parse(input).expect("Failed to parse input");
+
+    Day24::part_one(&valley)
+}
+
+pub fn star_two(input: &str) -> usize {
+    let valley = Day24::parse(input).expect("Failed to parse input");
+
+    Day24::part_two(&valley)
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Valley;
+    type PartOne = usize;
+    type PartTwo = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+
+    fn part_one(valley: &Self::Input) -> Self::PartOne {
+        valley
+            .trip(valley.start(), valley.goal(), 0)
+            .expect("The goal should be reachable")
+    }
+
+    fn part_two(valley: &Self::Input) -> Self::PartTwo {
+        let (start, goal) = (valley.start(), valley.goal());
+
+        [(start, goal), (goal, start), (start, goal)]
+            .into_iter()
+            .try_fold(0, |time, (from, to)| valley.trip(from, to, time))
+            .expect("Every trip should be possible")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bitset {
+    words: Vec<u64>,
+}
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    fn insert(&mut self, idx: usize) {
+        self.words[idx / 64] |= 1 << (idx % 64);
+    }
+
+    fn contains(&self, idx: usize) -> bool {
+        self.words[idx / 64] & (1 << (idx % 64)) != 0
+    }
+}
+
+/// The valley inside the walls, with positions relative to its top left corner. The entrance is
+/// in the row above and the exit in the row below.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Valley {
+    width: usize,
+    height: usize,
+    /// Columns of the gaps in the top and bottom walls.
+    entrance: usize,
+    exit: usize,
+    /// Where blizzards start in each row, by the column they start in.
+    right: Vec<Bitset>,
+    left: Vec<Bitset>,
+    /// Where blizzards start in each column, by the row they start in.
+    down: Vec<Bitset>,
+    up: Vec<Bitset>,
+}
+
+impl Valley {
+    fn start(&self) -> Vector2<isize> {
+        Vector2::new(self.entrance as isize, -1)
+    }
+
+    fn goal(&self) -> Vector2<isize> {
+        Vector2::new(self.exit as isize, self.height as isize)
+    }
+
+    /// Blizzards come back to where they started after this many minutes.
+    fn period(&self) -> usize {
+        fn gcd(a: usize, b: usize) -> usize {
+            if b == 0 {
+                a
+            } else {
+                gcd(b, a % b)
+            }
+        }
+
+        self.width / gcd(self.width, self.height) * self.height
+    }
+
+    /// Whether a blizzard is at `position`, which is inside the valley, after `minute` minutes.
+    ///
+    /// Blizzards move in straight lines and wrap around, so rather than simulating them, check
+    /// whether one started where it needs to have started to be here now.
+    fn blizzard(&self, position: Vector2<usize>, minute: usize) -> bool {
+        let (x, y) = (position.x, position.y);
+        let (dx, dy) = (minute % self.width, minute % self.height);
+
+        self.right[y].contains((x + self.width - dx) % self.width)
+            || self.left[y].contains((x + dx) % self.width)
+            || self.down[x].contains((y + self.height - dy) % self.height)
+            || self.up[x].contains((y + dy) % self.height)
+    }
+
+    /// Whether the expedition can be at `position` after `minute` minutes.
+    fn is_free(&self, position: Vector2<isize>, minute: usize) -> bool {
+        if position == self.start() || position == self.goal() {
+            return true;
+        }
+
+        let in_valley = (0..self.width as isize).contains(&position.x)
+            && (0..self.height as isize).contains(&position.y);
+
+        in_valley
+            && !self.blizzard(
+                Vector2::new(position.x as usize, position.y as usize),
+                minute,
+            )
+    }
+
+    /// The minute the expedition reaches `to` at the earliest, leaving `from` at minute `start`,
+    /// [`None`] if it never can.
+    ///
+    /// Breadth first search over positions and the time modulo the blizzards' period, as that's
+    /// all that matters for where the expedition can go next.
+    fn trip(&self, from: Vector2<isize>, to: Vector2<isize>, start: usize) -> Option<usize> {
+        let period = self.period();
+
+        let result = search::bfs(
+            [(from, start % period)],
+            |&(position, phase)| {
+                let next_phase = (phase + 1) % period;
+
+                MOVES
+                    .map(|m| (position + m, next_phase))
+                    .into_iter()
+                    .filter(move |&(next, _)| self.is_free(next, next_phase))
+            },
+            |&(position, _)| position == to,
+        );
+
+        result.goal_distance().map(|minutes| start + minutes)
+    }
 }
 
-pub fn star_two(input: &str) -> i64 {
-    0
+impl FromStr for Valley {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<_> = s.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        let [top, rows @ .., bottom] = &lines[..] else {
+            return Err("Expected the valley surrounded by walls".into());
+        };
+        let width = top.len().saturating_sub(2);
+        let height = rows.len();
+        if width == 0 || height == 0 {
+            return Err("The valley needs at least one row and column".into());
+        }
+
+        // The gap in a wall like `#.#####`
+        let gap = |line: &str| {
+            let is_wall = line.len() == width + 2 && line.chars().all(|c| c == '#' || c == '.');
+            let gaps: Vec<_> = line.match_indices('.').map(|(i, _)| i).collect();
+
+            match gaps[..] {
+                [gap] if is_wall && (1..=width).contains(&gap) => Ok(gap - 1),
+                _ => Err(Error::parse(s, line, "Expected a wall with a single gap")),
+            }
+        };
+        let (entrance, exit) = (gap(top)?, gap(bottom)?);
+
+        let mut valley = Self {
+            width,
+            height,
+            entrance,
+            exit,
+            right: vec![Bitset::new(width); height],
+            left: vec![Bitset::new(width); height],
+            down: vec![Bitset::new(height); width],
+            up: vec![Bitset::new(height); width],
+        };
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.len() != width + 2 || !row.starts_with('#') || !row.ends_with('#') {
+                return Err(Error::parse(
+                    s,
+                    row,
+                    format!("Expected {width} tiles between walls"),
+                ));
+            }
+
+            for (x, c) in row[1..row.len() - 1].char_indices() {
+                match c {
+                    '.' => {}
+                    '>' => valley.right[y].insert(x),
+                    '<' => valley.left[y].insert(x),
+                    'v' => valley.down[x].insert(y),
+                    '^' => valley.up[x].insert(y),
+                    _ => {
+                        return Err(Error::parse(
+                            s,
+                            &row[x + 1..x + 1 + c.len_utf8()],
+                            format!("Unknown tile `{c}`"),
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(valley)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{star_one, star_two};
+    use super::{star_one, star_two, Day24};
+    use crate::{math::Vector2, Error, Location, Solution};
+
+    const INPUT: &str = r#"
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+"#;
 
     #[test]
     fn test_star_one() {
-        assert_eq!(star_one(""), 1)
+        assert_eq!(star_one(INPUT), 18)
     }
 
     #[test]
     fn test_star_two() {
-        assert_eq!(star_two(""), 1)
+        assert_eq!(star_two(INPUT), 54)
+    }
+
+    #[test]
+    fn test_blizzard() {
+        let valley =
+            Day24::parse("#.#####\n#.....#\n#>....#\n#.....#\n#...v.#\n#.....#\n#####.#").unwrap();
+
+        assert_eq!(valley.period(), 5);
+        assert!(valley.blizzard(Vector2::new(0, 1), 0));
+        assert!(valley.blizzard(Vector2::new(3, 1), 3));
+        assert!(valley.blizzard(Vector2::new(0, 1), 5));
+        assert!(!valley.blizzard(Vector2::new(1, 1), 0));
+        // Wrapping from the bottom back to the top
+        assert!(valley.blizzard(Vector2::new(3, 0), 2));
+        assert!(valley.blizzard(Vector2::new(3, 0), 1002));
+        assert!(valley.blizzard(Vector2::new(3, 3), 1000));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT.replace("#.<..<<#", "#.<.x<<#");
+        let Err(Error::Parse(e)) = Day24::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(4, 5));
+
+        let input = INPUT.replace("######.#", "########");
+        let Err(Error::Parse(e)) = Day24::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(7, 1));
+
+        let input = INPUT.replace("#>v.><>#", "#>v.><#");
+        assert!(Day24::parse(&input).is_err());
     }
 }
@@ -0,0 +1,282 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+use crate::{try_parse_lines, Error, Solution};
+
+pub fn star_one(input: &str) -> String {
+    let requirements = Day25::parse(input).expect("Failed to parse input");
+
+    Day25::part_one(&requirements)
+}
+
+pub fn star_two(input: &str) -> &'static str {
+    let requirements = Day25::parse(input).expect("Failed to parse input");
+
+    Day25::part_two(&requirements)
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<Snafu>;
+    type PartOne = String;
+    type PartTwo = &'static str;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        try_parse_lines(input).collect()
+    }
+
+    fn part_one(requirements: &Self::Input) -> Self::PartOne {
+        Snafu::try_sum(requirements.iter().copied())
+            .expect("The total fuel requirement should fit in 128 bits")
+            .to_string()
+    }
+
+    fn part_two(_requirements: &Self::Input) -> Self::PartTwo {
+        // There's no puzzle for the last star
+        "Start the blender!"
+    }
+}
+
+/// A number in SNAFU, balanced base five with the digits `=` (-2), `-` (-1), `0`, `1`, and `2`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Snafu(i128);
+
+impl Snafu {
+    /// The sum, [`None`] if it doesn't fit in 128 bits.
+    pub fn checked_add(self, rhs: Snafu) -> Option<Snafu> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    /// Add up `numbers`, failing if the total doesn't fit in 128 bits.
+    pub fn try_sum<I>(numbers: I) -> Result<Snafu, Error>
+    where
+        I: IntoIterator<Item = Snafu>,
+    {
+        numbers.into_iter().try_fold(Snafu::default(), |total, n| {
+            total
+                .checked_add(n)
+                .ok_or_else(|| format!("{total} + {n} doesn't fit in 128 bits").into())
+        })
+    }
+
+    fn digit(c: char) -> Option<i128> {
+        match c {
+            '=' => Some(-2),
+            '-' => Some(-1),
+            '0' => Some(0),
+            '1' => Some(1),
+            '2' => Some(2),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("Expected at least one SNAFU digit".into());
+        }
+
+        s.char_indices()
+            .try_fold(0_i128, |value, (idx, c)| {
+                let digit = Self::digit(c).ok_or_else(|| {
+                    Error::parse(
+                        s,
+                        &s[idx..idx + c.len_utf8()],
+                        format!("Unknown SNAFU digit `{c}`"),
+                    )
+                })?;
+
+                value
+                    .checked_mul(5)
+                    .and_then(|v| v.checked_add(digit))
+                    .ok_or_else(|| Error::parse(s, s, "SNAFU number doesn't fit in 128 bits"))
+            })
+            .map(Self)
+    }
+}
+
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return write!(f, "0");
+        }
+
+        let mut digits = vec![];
+        let mut value = self.0;
+        while value != 0 {
+            let (mut quotient, mut remainder) = (value.div_euclid(5), value.rem_euclid(5));
+            // 3 and 4 are written as 5 - 2 and 5 - 1, carrying the 5 to the next digit
+            if remainder > 2 {
+                remainder -= 5;
+                quotient += 1;
+            }
+
+            digits.push(match remainder {
+                -2 => '=',
+                -1 => '-',
+                0 => '0',
+                1 => '1',
+                _ => '2',
+            });
+            value = quotient;
+        }
+
+        write!(f, "{}", digits.iter().rev().collect::<String>())
+    }
+}
+
+/// **Note:** Panics if the sum doesn't fit in 128 bits, see [`Snafu::checked_add`].
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, rhs: Snafu) -> Self::Output {
+        self.checked_add(rhs)
+            .unwrap_or_else(|| panic!("{self} + {rhs} doesn't fit in 128 bits"))
+    }
+}
+
+impl Sum for Snafu {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Snafu::default(), Add::add)
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(value: i64) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<i128> for Snafu {
+    fn from(value: i128) -> Self {
+        Self(value)
+    }
+}
+
+impl TryFrom<Snafu> for i64 {
+    type Error = Error;
+
+    fn try_from(value: Snafu) -> Result<Self, Self::Error> {
+        value
+            .0
+            .try_into()
+            .map_err(|_| format!("{value} doesn't fit in 64 bits").into())
+    }
+}
+
+impl From<Snafu> for i128 {
+    fn from(value: Snafu) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{star_one, Day25, Snafu};
+    use crate::{Error, Location, Solution};
+
+    const INPUT: &str = r#"
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122
+"#;
+
+    #[test]
+    fn test_star_one() {
+        assert_eq!(star_one(INPUT), "2=-1=0")
+    }
+
+    #[test]
+    fn test_conversions() {
+        let examples: [(i64, &str); 10] = [
+            (1, "1"),
+            (3, "1="),
+            (8, "2="),
+            (10, "20"),
+            (15, "1=0"),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+            (-3, "-2"),
+            (0, "0"),
+        ];
+
+        for (decimal, snafu) in examples {
+            assert_eq!(Snafu::from(decimal).to_string(), snafu);
+            assert_eq!(
+                i64::try_from(snafu.parse::<Snafu>().unwrap()).unwrap(),
+                decimal
+            );
+        }
+
+        assert!(i64::try_from(Snafu::from(i128::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = INPUT.replace("2=01", "2=31");
+        let Err(Error::Parse(e)) = Day25::parse(&input) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(6, 3));
+
+        assert!("2".repeat(60).parse::<Snafu>().is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        let (max, one) = (Snafu::from(i128::MAX), Snafu::from(1_i64));
+
+        assert_eq!(max.checked_add(one), None);
+        assert!(Snafu::try_sum([max, one]).is_err());
+        assert_eq!(Snafu::try_sum([Snafu::from(-1_i64), max, one]), Ok(max));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in 128 bits")]
+    fn test_add_overflow() {
+        let _ = Snafu::from(i128::MIN) + Snafu::from(-1_i64);
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip_i64(n: i64) {
+            let snafu = Snafu::from(n);
+
+            prop_assert_eq!(snafu.to_string().parse::<Snafu>().unwrap(), snafu);
+            prop_assert_eq!(i64::try_from(snafu).unwrap(), n);
+        }
+
+        #[test]
+        fn test_round_trip_i128(n: i128) {
+            let snafu = Snafu::from(n);
+
+            prop_assert_eq!(i128::from(snafu.to_string().parse::<Snafu>().unwrap()), n);
+        }
+
+        #[test]
+        fn test_add(a: i64, b: i64) {
+            let sum = Snafu::from(a) + Snafu::from(b);
+
+            prop_assert_eq!(i128::from(sum), a as i128 + b as i128);
+        }
+    }
+}
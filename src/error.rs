@@ -0,0 +1,182 @@
+use std::fmt;
+use std::num::ParseIntError;
+
+/// The crate wide error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The input could not be parsed, with the location where it broke.
+    Parse(ParseError),
+    /// The input was well formed, but doesn't describe a valid puzzle, e.g. a missing start
+    /// location.
+    Invalid(String),
+}
+
+impl Error {
+    /// A parse error for `part` of `s`, where `part` is a sub slice of `s`.
+    ///
+    /// The location is relative to `s`, use [`Error::offset`] to make it relative to a larger
+    /// input.
+    pub fn parse(s: &str, part: &str, reason: impl Into<String>) -> Self {
+        Self::Parse(ParseError::new(s, part, reason))
+    }
+
+    /// Offset the location of a parse error by a given location.
+    ///
+    /// Errors without a location are turned into parse errors pointing at `location`.
+    pub fn offset(self, location: Location, text: &str) -> Self {
+        match self {
+            Self::Parse(e) => Self::Parse(e.offset(location)),
+            Self::Invalid(reason) => Self::Parse(ParseError {
+                location,
+                text: text.to_owned(),
+                reason,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<String> for Error {
+    fn from(reason: String) -> Self {
+        Self::Invalid(reason)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(reason: &str) -> Self {
+        Self::Invalid(reason.to_owned())
+    }
+}
+
+impl From<ParseIntError> for Error {
+    fn from(e: ParseIntError) -> Self {
+        Self::Invalid(e.to_string())
+    }
+}
+
+/// A location in the input, both line and column are 1 indexed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    /// The location of the byte at `offset` within `input`.
+    pub fn of_offset(input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+
+        Self { line, column }
+    }
+
+    /// The location of `part` within `input`, where `part` is a sub slice of `input`.
+    ///
+    /// Falls back to the start of `input` if `part` isn't a sub slice of it.
+    pub fn of(input: &str, part: &str) -> Self {
+        let start = input.as_ptr() as usize;
+        let part_start = part.as_ptr() as usize;
+
+        if part_start >= start && part_start + part.len() <= start + input.len() {
+            Self::of_offset(input, part_start - start)
+        } else {
+            Self::new(1, 1)
+        }
+    }
+
+    /// Treat `self` as relative to `base`.
+    fn offset(self, base: Location) -> Self {
+        if self.line == 1 {
+            Self::new(base.line, base.column + self.column - 1)
+        } else {
+            Self::new(base.line + self.line - 1, self.column)
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub location: Location,
+    /// The offending text.
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(s: &str, part: &str, reason: impl Into<String>) -> Self {
+        Self {
+            location: Location::of(s, part),
+            text: part.to_owned(),
+            reason: reason.into(),
+        }
+    }
+
+    fn offset(self, base: Location) -> Self {
+        Self {
+            location: self.location.offset(base),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} at `{}`", self.location, self.reason, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Location};
+
+    #[test]
+    fn test_location_of() {
+        let input = "abc\ndef\n  ghi";
+
+        assert_eq!(Location::of(input, &input[0..1]), Location::new(1, 1));
+        assert_eq!(Location::of(input, &input[5..7]), Location::new(2, 2));
+        assert_eq!(Location::of(input, &input[10..]), Location::new(3, 3));
+        assert_eq!(Location::of(input, "abc"), Location::new(1, 1));
+    }
+
+    #[test]
+    fn test_offset() {
+        let line = "move x from 1 to 2";
+        let error = Error::parse(line, &line[5..6], "Invalid count");
+
+        let Error::Parse(e) = error.offset(Location::new(4, 3), line) else {
+            panic!("Expected a parse error");
+        };
+
+        assert_eq!(e.location, Location::new(4, 8));
+        assert_eq!(e.text, "x");
+    }
+}
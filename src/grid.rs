@@ -0,0 +1,577 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::{math::Vector2, Error};
+
+/// Parse a character map, calling `tile` with the line, position and character of every cell.
+///
+/// Empty lines are skipped and trailing whitespace is trimmed, leading whitespace is kept so
+/// indented rows stay in their columns. Characters `tile` returns [`None`] for are reported as
+/// errors located within `input`.
+fn parse_map<'a, F>(input: &'a str, mut tile: F) -> Result<(), Error>
+where
+    F: FnMut(&'a str, Vector2<isize>, char) -> Option<()>,
+{
+    for (y, line) in input
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.is_empty())
+        .enumerate()
+    {
+        for (x, (idx, c)) in line.char_indices().enumerate() {
+            if tile(line, Vector2::new(x as isize, y as isize), c).is_none() {
+                return Err(Error::parse(
+                    input,
+                    &line[idx..idx + c.len_utf8()],
+                    format!("Unexpected character `{c}`"),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A dense rectangular grid, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of `width` by `height` cells, given row by row.
+    ///
+    /// **Note:** Panics if there isn't exactly one value per cell.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "Expected {width}x{height} cells"
+        );
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// A grid of `width` by `height` cells all set to `value`.
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    /// Parse a rectangular character map, `tile` turns each character into a cell or returns
+    /// [`None`] if it's not expected.
+    pub fn parse<F>(input: &str, mut tile: F) -> Result<Self, Error>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut cells = vec![];
+        let mut rows = vec![];
+
+        parse_map(input, |line, position, c| {
+            if position.x == 0 {
+                rows.push(line);
+            }
+
+            cells.push(tile(c)?);
+            Some(())
+        })?;
+
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if let Some(row) = rows.iter().find(|row| row.chars().count() != width) {
+            return Err(Error::parse(
+                input,
+                row,
+                format!("Expected {width} tiles like the first row"),
+            ));
+        }
+
+        Ok(Self::new(width, rows.len(), cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, position: Vector2<isize>) -> bool {
+        (0..self.width as isize).contains(&position.x)
+            && (0..self.height as isize).contains(&position.y)
+    }
+
+    pub fn get(&self, position: Vector2<isize>) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[self.index(position)])
+    }
+
+    pub fn get_mut(&mut self, position: Vector2<isize>) -> Option<&mut T> {
+        if self.contains(position) {
+            let idx = self.index(position);
+
+            Some(&mut self.cells[idx])
+        } else {
+            None
+        }
+    }
+
+    /// Every position in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Vector2<isize>> {
+        let (width, height) = (self.width as isize, self.height as isize);
+
+        (0..height).flat_map(move |y| (0..width).map(move |x| Vector2::new(x, y)))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        self.positions().zip(&self.cells)
+    }
+
+    /// The orthogonal neighbours of `position` that are inside the grid.
+    pub fn neighbours4(
+        &self,
+        position: Vector2<isize>,
+    ) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        self.neighbours(position.neighbours4())
+    }
+
+    /// The orthogonal and diagonal neighbours of `position` that are inside the grid.
+    pub fn neighbours8(
+        &self,
+        position: Vector2<isize>,
+    ) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        self.neighbours(position.neighbours8())
+    }
+
+    fn neighbours<I>(&self, positions: I) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_
+    where
+        I: Iterator<Item = Vector2<isize>> + 'static,
+    {
+        positions.filter_map(|neighbour| self.get(neighbour).map(|cell| (neighbour, cell)))
+    }
+
+    /// The cells of row `y`, left to right. Empty if `y` is outside the grid.
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> + '_ {
+        let cells = if y < self.height {
+            &self.cells[y * self.width..(y + 1) * self.width]
+        } else {
+            &[]
+        };
+
+        cells.iter()
+    }
+
+    /// The cells of column `x`, top to bottom. Empty if `x` is outside the grid.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    /// The cells from `from`, exclusive, stepping by `direction` until leaving the grid.
+    ///
+    /// `from` may be outside the grid, for example to scan a whole row from just before its
+    /// start. Empty if `direction` is zero, as it would never leave.
+    pub fn ray(
+        &self,
+        from: Vector2<isize>,
+        direction: Vector2<isize>,
+    ) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        steps(from, direction).map_while(move |p| self.get(p).map(|cell| (p, cell)))
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    fn index(&self, position: Vector2<isize>) -> usize {
+        position.y as usize * self.width + position.x as usize
+    }
+}
+
+impl<T> Index<Vector2<isize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Vector2<isize>) -> &Self::Output {
+        self.get(position)
+            .unwrap_or_else(|| panic!("{position:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Vector2<isize>> for Grid<T> {
+    fn index_mut(&mut self, position: Vector2<isize>) -> &mut Self::Output {
+        self.get_mut(position)
+            .unwrap_or_else(|| panic!("{position:?} is outside the grid"))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height {
+            for cell in self.row(y) {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An unbounded grid where only some cells are set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Vector2<isize>, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Parse a character map, where `empty` marks cells that aren't set and `tile` turns any
+    /// other character into a cell or returns [`None`] if it's not expected.
+    pub fn parse<F>(input: &str, empty: char, mut tile: F) -> Result<Self, Error>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut grid = Self::new();
+
+        parse_map(input, |_, position, c| {
+            if c != empty {
+                grid.insert(position, tile(c)?);
+            }
+
+            Some(())
+        })?;
+
+        Ok(grid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, position: Vector2<isize>) -> bool {
+        self.cells.contains_key(&position)
+    }
+
+    pub fn get(&self, position: Vector2<isize>) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    pub fn get_mut(&mut self, position: Vector2<isize>) -> Option<&mut T> {
+        self.cells.get_mut(&position)
+    }
+
+    /// Set a cell, returning what was there before.
+    pub fn insert(&mut self, position: Vector2<isize>, value: T) -> Option<T> {
+        self.cells.insert(position, value)
+    }
+
+    pub fn remove(&mut self, position: Vector2<isize>) -> Option<T> {
+        self.cells.remove(&position)
+    }
+
+    /// The cells that are set, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        self.cells.iter().map(|(&p, cell)| (p, cell))
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.cells.values()
+    }
+
+    /// The smallest and largest coordinates of the cells that are set, [`None`] if there are
+    /// none.
+    pub fn bounds(&self) -> Option<(Vector2<isize>, Vector2<isize>)> {
+        let mut positions = self.cells.keys();
+        let first = *positions.next()?;
+
+        Some(positions.fold((first, first), |(min, max), p| {
+            (
+                Vector2::new(min.x.min(p.x), min.y.min(p.y)),
+                Vector2::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        }))
+    }
+
+    /// The orthogonal neighbours of `position` that are set.
+    pub fn neighbours4(
+        &self,
+        position: Vector2<isize>,
+    ) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        self.neighbours(position.neighbours4())
+    }
+
+    /// The orthogonal and diagonal neighbours of `position` that are set.
+    pub fn neighbours8(
+        &self,
+        position: Vector2<isize>,
+    ) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_ {
+        self.neighbours(position.neighbours8())
+    }
+
+    fn neighbours<I>(&self, positions: I) -> impl Iterator<Item = (Vector2<isize>, &T)> + '_
+    where
+        I: Iterator<Item = Vector2<isize>> + 'static,
+    {
+        positions.filter_map(|neighbour| self.get(neighbour).map(|cell| (neighbour, cell)))
+    }
+
+    /// The cells of row `y` within the bounds, left to right.
+    ///
+    /// Finding the bounds visits every cell, prefer [`SparseGrid::get`] for many rows.
+    pub fn row(&self, y: isize) -> impl Iterator<Item = Option<&T>> + '_ {
+        let (min, max) = self.bounds().unzip();
+        let xs = min.map(|m| m.x).unwrap_or(0)..max.map(|m| m.x + 1).unwrap_or(0);
+
+        xs.map(move |x| self.get(Vector2::new(x, y)))
+    }
+
+    /// The cells of column `x` within the bounds, top to bottom.
+    ///
+    /// Finding the bounds visits every cell, prefer [`SparseGrid::get`] for many columns.
+    pub fn column(&self, x: isize) -> impl Iterator<Item = Option<&T>> + '_ {
+        let (min, max) = self.bounds().unzip();
+        let ys = min.map(|m| m.y).unwrap_or(0)..max.map(|m| m.y + 1).unwrap_or(0);
+
+        ys.map(move |y| self.get(Vector2::new(x, y)))
+    }
+
+    /// The cells from `from`, exclusive, stepping by `direction` until leaving the bounds.
+    ///
+    /// Empty if `direction` is zero, as it would never leave.
+    pub fn ray(
+        &self,
+        from: Vector2<isize>,
+        direction: Vector2<isize>,
+    ) -> impl Iterator<Item = (Vector2<isize>, Option<&T>)> + '_ {
+        let bounds = self.bounds();
+        let in_bounds = move |p: &Vector2<isize>| {
+            bounds.is_some_and(|(min, max)| {
+                (min.x..=max.x).contains(&p.x) && (min.y..=max.y).contains(&p.y)
+            })
+        };
+
+        steps(from, direction)
+            .skip_while(move |p| !in_bounds(p) && approaching(bounds, *p, direction))
+            .take_while(in_bounds)
+            .map(move |p| (p, self.get(p)))
+    }
+}
+
+/// The positions from `from`, exclusive, stepping by `direction` forever, or none for a zero
+/// `direction`.
+fn steps(from: Vector2<isize>, direction: Vector2<isize>) -> impl Iterator<Item = Vector2<isize>> {
+    let first = (direction != Vector2::default()).then(|| from + direction);
+
+    std::iter::successors(first, move |&p| Some(p + direction))
+}
+
+/// Whether stepping from `position` by `direction` gets closer to the bounds on both axes, so a
+/// ray starting outside them may still enter.
+fn approaching(
+    bounds: Option<(Vector2<isize>, Vector2<isize>)>,
+    position: Vector2<isize>,
+    direction: Vector2<isize>,
+) -> bool {
+    let Some((min, max)) = bounds else {
+        return false;
+    };
+    let axis = |p: isize, d: isize, min: isize, max: isize| {
+        (p < min && d > 0) || (p > max && d < 0) || (min..=max).contains(&p)
+    };
+
+    axis(position.x, direction.x, min.x, max.x) && axis(position.y, direction.y, min.y, max.y)
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(Vector2<isize>, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Vector2<isize>, T)>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Index<Vector2<isize>> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, position: Vector2<isize>) -> &Self::Output {
+        self.get(position)
+            .unwrap_or_else(|| panic!("{position:?} isn't set"))
+    }
+}
+
+/// Renders the bounds, with `.` for cells that aren't set.
+impl<T: fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some((min, max)) = self.bounds() else {
+            return Ok(());
+        };
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                match self.get(Vector2::new(x, y)) {
+                    Some(cell) => write!(f, "{cell}")?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, SparseGrid};
+    use crate::{math::Vector2, Error, Location};
+
+    const INPUT: &str = r#"
+123
+456
+789
+"#;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(input, |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits(INPUT);
+
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid[Vector2::new(2, 1)], 6);
+        assert_eq!(grid.get(Vector2::new(3, 1)), None);
+        assert_eq!(grid.to_string(), "123\n456\n789\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let Err(Error::Parse(e)) = Grid::parse("123\n4x6", |c| c.to_digit(10)) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 2));
+
+        let Err(Error::Parse(e)) = Grid::parse("123\n45\n789", |c| c.to_digit(10)) else {
+            panic!("Expected a parse error");
+        };
+        assert_eq!(e.location, Location::new(2, 1));
+    }
+
+    #[test]
+    fn test_parse_indented() {
+        let grid = Grid::parse("ab\n c\n", Some).unwrap();
+        assert_eq!(grid[Vector2::new(0, 1)], ' ');
+        assert_eq!(grid[Vector2::new(1, 1)], 'c');
+
+        let grid = SparseGrid::parse("#\n  #  \n", ' ', |c| (c == '#').then_some(c)).unwrap();
+        assert!(grid.contains(Vector2::new(2, 1)));
+        assert_eq!(grid.to_string(), "#..\n..#\n");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = digits(INPUT);
+        let values = |cells: Vec<(Vector2<isize>, &u32)>| -> Vec<u32> {
+            cells.into_iter().map(|(_, &v)| v).collect()
+        };
+
+        assert_eq!(
+            values(grid.neighbours4(Vector2::new(0, 0)).collect()),
+            [2, 4]
+        );
+        assert_eq!(
+            values(grid.neighbours8(Vector2::new(1, 1)).collect()),
+            [2, 3, 6, 9, 8, 7, 4, 1]
+        );
+        assert_eq!(
+            values(grid.neighbours8(Vector2::new(2, 2)).collect()),
+            [6, 8, 5]
+        );
+    }
+
+    #[test]
+    fn test_rows_columns_and_rays() {
+        let grid = digits(INPUT);
+
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [3, 6, 9]);
+        assert_eq!(grid.row(3).count(), 0);
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(grid.column(4).count(), 0);
+
+        let ray: Vec<_> = grid
+            .ray(Vector2::new(0, 0), Vector2::new(1, 1))
+            .map(|(_, &v)| v)
+            .collect();
+        assert_eq!(ray, [5, 9]);
+
+        // Starting just outside the grid
+        let ray: Vec<_> = grid
+            .ray(Vector2::new(1, 3), Vector2::new(0, -1))
+            .map(|(_, &v)| v)
+            .collect();
+        assert_eq!(ray, [8, 5, 2]);
+
+        // A zero step never leaves the grid, so it yields nothing
+        assert_eq!(grid.ray(Vector2::new(1, 1), Vector2::new(0, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let grid = SparseGrid::parse("#..\n.#.\n..#", '.', |c| (c == '#').then_some('#')).unwrap();
+
+        assert_eq!(grid.len(), 3);
+        assert_eq!(
+            grid.bounds(),
+            Some((Vector2::new(0, 0), Vector2::new(2, 2)))
+        );
+        assert_eq!(grid.neighbours8(Vector2::new(1, 1)).count(), 2);
+        assert_eq!(grid.neighbours4(Vector2::new(1, 1)).count(), 0);
+        assert_eq!(grid.row(1).collect::<Vec<_>>(), [None, Some(&'#'), None]);
+        assert_eq!(grid.to_string(), "#..\n.#.\n..#\n");
+
+        let ray: Vec<_> = grid
+            .ray(Vector2::new(-1, -1), Vector2::new(1, 1))
+            .map(|(_, cell)| cell.is_some())
+            .collect();
+        assert_eq!(ray, [true, true, true]);
+        assert_eq!(grid.ray(Vector2::new(1, 1), Vector2::new(0, 0)).count(), 0);
+
+        assert!(SparseGrid::parse("#.x", '.', |c| (c == '#').then_some(())).is_err());
+    }
+}
@@ -1,35 +1,155 @@
 #![allow(dead_code, unused)]
+use std::fmt;
 use std::fs::File;
 use std::io::Read;
 use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
 #[macro_use]
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+pub mod bench;
+mod circular;
+mod error;
+mod grid;
 mod math;
+mod search;
+
+pub use error::{Error, Location, ParseError};
+
+/// A single day's puzzle.
+///
+/// The input is parsed once into [`Solution::Input`] which both parts are then answered from.
+pub trait Solution {
+    /// The typed model of the puzzle input.
+    type Input;
+    /// The answer to part one.
+    type PartOne: fmt::Display;
+    /// The answer to part two.
+    type PartTwo: fmt::Display;
+
+    fn parse(input: &str) -> Result<Self::Input, Error>;
+
+    fn part_one(input: &Self::Input) -> Self::PartOne;
+
+    fn part_two(input: &Self::Input) -> Self::PartTwo;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+/// Object safe version of [`Solution`], used to store the solutions in [`DAYS`].
+pub trait DynSolution: Sync {
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, Error>;
+
+    fn solve(&self, part: Part, input: &str) -> Result<String, Error> {
+        let parsed = self.parse(input)?;
+
+        Ok(parsed.solve(part))
+    }
+}
+
+/// A parsed puzzle input that can answer either part.
+pub trait Parsed {
+    fn part_one(&self) -> String;
+
+    fn part_two(&self) -> String;
+
+    fn solve(&self, part: Part) -> String {
+        match part {
+            Part::One => self.part_one(),
+            Part::Two => self.part_two(),
+        }
+    }
+}
+
+struct Model<S: Solution>(S::Input);
+
+impl<S: Solution> Parsed for Model<S> {
+    fn part_one(&self) -> String {
+        S::part_one(&self.0).to_string()
+    }
+
+    fn part_two(&self) -> String {
+        S::part_two(&self.0).to_string()
+    }
+}
+
+impl<S> DynSolution for S
+where
+    S: Solution + Sync + 'static,
+    S::Input: 'static,
+{
+    fn parse(&self, input: &str) -> Result<Box<dyn Parsed>, Error> {
+        let parsed = S::parse(input)?;
+
+        Ok(Box::new(Model::<S>(parsed)))
+    }
+}
+
+/// All the days, indexed by day - 1. Days that have not been solved are [`None`].
+pub static DAYS: [Option<&dyn DynSolution>; 25] = [
+    Some(&day01::Day01),
+    Some(&day02::Day02),
+    Some(&day03::Day03),
+    Some(&day04::Day04),
+    Some(&day05::Day05),
+    Some(&day06::Day06),
+    Some(&day07::Day07),
+    Some(&day08::Day08),
+    Some(&day09::Day09),
+    Some(&day10::Day10),
+    Some(&day11::Day11),
+    Some(&day12::Day12),
+    Some(&day13::Day13),
+    Some(&day14::Day14),
+    Some(&day15::Day15),
+    Some(&day16::Day16),
+    Some(&day17::Day17),
+    Some(&day18::Day18),
+    Some(&day19::Day19),
+    Some(&day20::Day20),
+    Some(&day21::Day21),
+    Some(&day22::Day22),
+    Some(&day23::Day23),
+    Some(&day24::Day24),
+    Some(&day25::Day25),
+];
+
+/// Look up the solution for a given day, 1 indexed.
+pub fn day(day: u8) -> Option<&'static dyn DynSolution> {
+    (day as usize)
+        .checked_sub(1)
+        .and_then(|idx| DAYS.get(idx))
+        .copied()
+        .flatten()
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct DigitIterator {
@@ -67,92 +187,119 @@ impl Iterator for DigitIterator {
     }
 }
 
-fn time<F>(label: &str, closure: F)
+/// Parse lines of text into custom types.
+///
+/// Each line is treated as parsable after trimming.
+///
+/// **Note:** Panics if any parsing fails, see [`try_parse_lines`] for a fallible version.
+pub fn parse_lines<'a, T>(input: &'a str) -> impl Iterator<Item = T> + 'a
 where
-    F: Fn(),
+    T: FromStr + 'a,
+    <T as FromStr>::Err: Into<Error>,
 {
-    let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    closure();
-    let end = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-    let time = end - start;
-    println!(
-        "Time taken for {}: {}s and {}ns",
-        label,
-        time.as_secs(),
-        time.subsec_nanos()
-    );
+    try_parse_lines(input).map(expect_parsed)
 }
 
 /// Parse lines of text into custom types.
 ///
-/// Each line is treated as parsable after trimming.
-///
-/// **Note:** Panics if any parsing fails
-pub fn parse_lines<T>(input: &str) -> impl Iterator<Item = T> + '_
+/// Each line is treated as parsable after trimming. Errors carry the location, within `input`,
+/// of the line that failed to parse.
+pub fn try_parse_lines<T>(input: &str) -> impl Iterator<Item = Result<T, Error>> + '_
 where
-    T: FromStr + std::fmt::Debug,
-    <T as FromStr>::Err: std::fmt::Debug,
+    T: FromStr,
+    <T as FromStr>::Err: Into<Error>,
 {
     input
         .lines()
         .map(str::trim)
-        .filter(|l| l.len() > 0)
-        .map(|l| {
-            l.parse().expect(&format!(
-                "Expected to be able to parse `{:?}` as `{:?}`",
-                l,
-                std::any::type_name::<T>()
-            ))
-        })
+        .filter(|l| !l.is_empty())
+        .map(move |l| parse_located(input, l))
 }
 
 /// Parse whitespace separated custom types.
 ///
 /// Each unit separated by whitespace is treated as parsable after trimming.
 ///
-/// **Note:** Panics if any parsing fails
-pub fn parse_whitespace_separated<T>(input: &str) -> impl Iterator<Item = T> + '_
+/// **Note:** Panics if any parsing fails, see [`try_parse_whitespace_separated`] for a fallible
+/// version.
+pub fn parse_whitespace_separated<'a, T>(input: &'a str) -> impl Iterator<Item = T> + 'a
+where
+    T: FromStr + 'a,
+    <T as FromStr>::Err: Into<Error>,
+{
+    try_parse_whitespace_separated(input).map(expect_parsed)
+}
+
+/// Parse whitespace separated custom types.
+///
+/// Each unit separated by whitespace is treated as parsable after trimming. Errors carry the
+/// location, within `input`, of the unit that failed to parse.
+pub fn try_parse_whitespace_separated<T>(input: &str) -> impl Iterator<Item = Result<T, Error>> + '_
 where
-    T: FromStr + std::fmt::Debug,
-    <T as FromStr>::Err: std::fmt::Debug,
+    T: FromStr,
+    <T as FromStr>::Err: Into<Error>,
 {
     input
         .split_whitespace()
         .map(str::trim)
-        .filter(|l| l.len() > 0)
-        .map(|l| {
-            l.parse().expect(&format!(
-                "Expected to be able to parse `{:?}` as `{:?}`",
-                l,
-                std::any::type_name::<T>()
-            ))
-        })
+        .filter(|l| !l.is_empty())
+        .map(move |l| parse_located(input, l))
 }
 
 /// Parse custom separator separated custom types.
 ///
 /// Each unit separated by a specific separator is treated as parsable after trimming.
 ///
-/// **Note:** Panics if any parsing fails
+/// **Note:** Panics if any parsing fails, see [`try_parse_custom_separated`] for a fallible
+/// version.
 pub fn parse_custom_separated<'a, T>(
     input: &'a str,
     separator: &'a str,
 ) -> impl Iterator<Item = T> + 'a
 where
-    T: FromStr + std::fmt::Debug,
-    <T as FromStr>::Err: std::fmt::Debug,
+    T: FromStr + 'a,
+    <T as FromStr>::Err: Into<Error>,
+{
+    try_parse_custom_separated(input, separator).map(expect_parsed)
+}
+
+/// Parse custom separator separated custom types.
+///
+/// Each unit separated by a specific separator is treated as parsable after trimming. Errors
+/// carry the location, within `input`, of the unit that failed to parse.
+pub fn try_parse_custom_separated<'a, T>(
+    input: &'a str,
+    separator: &'a str,
+) -> impl Iterator<Item = Result<T, Error>> + 'a
+where
+    T: FromStr,
+    <T as FromStr>::Err: Into<Error>,
 {
     input
         .split(separator)
         .map(str::trim)
-        .filter(|l| l.len() > 0)
-        .map(|l| {
-            l.parse().expect(&format!(
-                "Expected to be able to parse `{:?}` as `{:?}`",
-                l,
-                std::any::type_name::<T>()
-            ))
-        })
+        .filter(|l| !l.is_empty())
+        .map(move |l| parse_located(input, l))
+}
+
+/// Parse `part`, a sub slice of `input`, making any error relative to `input`.
+fn parse_located<T>(input: &str, part: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Into<Error>,
+{
+    part.parse()
+        .map_err(|e: T::Err| e.into().offset(Location::of(input, part), part))
+}
+
+fn expect_parsed<T>(result: Result<T, Error>) -> T {
+    result.unwrap_or_else(|e| {
+        panic!(
+            "Expected to be able to parse `{}`: {}",
+            std::any::type_name::<T>(),
+            e
+        )
+    })
 }
 
 pub fn load_file(path: &str) -> String {
@@ -176,6 +323,33 @@ mod tests {
         input
     }
 
+    #[test]
+    fn try_parse_lines_location() {
+        use crate::{try_parse_lines, Error, Location};
+
+        let result: Result<Vec<u64>, _> = try_parse_lines("1\n\n  2\n  x3\n").collect();
+
+        let Err(Error::Parse(e)) = result else {
+            panic!("Expected a parse error, got {:?}", result);
+        };
+        assert_eq!(e.location, Location::new(4, 3));
+        assert_eq!(e.text, "x3");
+    }
+
+    #[test]
+    fn registry() {
+        use crate::{day, Part};
+
+        let input = load_file("day01.txt");
+        let solution = day(1).expect("Day 1 should be registered");
+
+        assert_eq!(solution.solve(Part::One, &input), Ok("66616".to_owned()));
+        assert_eq!(solution.solve(Part::Two, &input), Ok("199172".to_owned()));
+        assert!(day(0).is_none());
+        assert!(day(25).is_some());
+        assert!(day(26).is_none());
+    }
+
     #[test]
     fn solve_day01() {
         use crate::day01::{star_one, star_two};
@@ -208,17 +382,12 @@ mod tests {
 
     #[test]
     fn solve_day04() {
-        use super::time;
         use crate::day04::{star_one, star_two};
 
         let input = load_file("day04.txt");
 
-        time("Day 04 start one", || {
-            assert_eq!(star_one(&input), 413);
-        });
-        time("Day 04 start one", || {
-            assert_eq!(star_two(&input), 806);
-        });
+        assert_eq!(star_one(&input), 413);
+        assert_eq!(star_two(&input), 806);
     }
 
     #[test]
@@ -294,84 +463,12 @@ mod tests {
 
     #[test]
     fn solve_day11() {
-        use crate::day11::prelude::*;
-
-        let monkeys = monkeys!(
-            {
-            Monkey 0:
-              Starting items: 84, 66, 62, 69, 88, 91, 91
-              Operation: new = old * 11
-              Test: divisible by 2
-                If true: throw to monkey 4
-                If false: throw to monkey 7
-            }
-
-            {
-            Monkey 1:
-              Starting items: 98, 50, 76, 99
-              Operation: new = old * old
-              Test: divisible by 7
-                If true: throw to monkey 3
-                If false: throw to monkey 6
-            }
-
-            {
-            Monkey 2:
-              Starting items: 72, 56, 94
-              Operation: new = old + 1
-              Test: divisible by 13
-                If true: throw to monkey 4
-                If false: throw to monkey 0
-            }
-
-            {
-            Monkey 3:
-              Starting items: 55, 88, 90, 77, 60, 67
-              Operation: new = old + 2
-              Test: divisible by 3
-                If true: throw to monkey 6
-                If false: throw to monkey 5
-            }
-
-            {
-            Monkey 4:
-              Starting items: 69, 72, 63, 60, 72, 52, 63, 78
-              Operation: new = old * 13
-              Test: divisible by 19
-                If true: throw to monkey 1
-                If false: throw to monkey 7
-            }
-
-            {
-            Monkey 5:
-              Starting items: 89, 73
-              Operation: new = old + 5
-              Test: divisible by 17
-                If true: throw to monkey 2
-                If false: throw to monkey 0
-            }
-
-            {
-            Monkey 6:
-              Starting items: 78, 68, 98, 88, 66
-              Operation: new = old + 6
-              Test: divisible by 11
-                If true: throw to monkey 2
-                If false: throw to monkey 5
-            }
-
-            {
-            Monkey 7:
-              Starting items: 70
-              Operation: new = old + 7
-              Test: divisible by 5
-                If true: throw to monkey 1
-                If false: throw to monkey 3
-            }
-        );
+        use crate::day11::{star_one, star_two};
+
+        let input = load_file("day11.txt");
 
-        assert_eq!(star_one(monkeys.clone()), 99840);
-        assert_eq!(star_two(monkeys), 20683044837);
+        assert_eq!(star_one(&input), 99840);
+        assert_eq!(star_two(&input), 20683044837);
     }
 
     #[test]
@@ -404,103 +501,13 @@ mod tests {
         assert_eq!(star_two(&input), 25771);
     }
 
-    #[test]
-    fn solve_day15() {
-        use crate::day15::{star_one, star_two};
-
-        let input = load_file("day15.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
-
-    #[test]
-    fn solve_day16() {
-        use crate::day16::{star_one, star_two};
 
-        let input = load_file("day16.txt");
 
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
 
-    #[test]
-    fn solve_day17() {
-        use crate::day17::{star_one, star_two};
 
-        let input = load_file("day17.txt");
 
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
 
-    #[test]
-    fn solve_day18() {
-        use crate::day18::{star_one, star_two};
 
-        let input = load_file("day18.txt");
 
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
 
-    #[test]
-    fn solve_day19() {
-        use crate::day19::{star_one, star_two};
-
-        let input = load_file("day19.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
-
-    #[test]
-    fn solve_day20() {
-        use crate::day20::{star_one, star_two};
-
-        let input = load_file("day20.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
-
-    #[test]
-    fn solve_day21() {
-        use crate::day21::{star_one, star_two};
-
-        let input = load_file("day21.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
-
-    #[test]
-    fn solve_day22() {
-        use crate::day22::{star_one, star_two};
-
-        let input = load_file("day22.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
-
-    #[test]
-    fn solve_day23() {
-        use crate::day23::{star_one, star_two};
-
-        let input = load_file("day23.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
-
-    #[test]
-    fn solve_day24() {
-        use crate::day24::{star_one, star_two};
-
-        let input = load_file("day24.txt");
-
-        assert_eq!(star_one(&input), 1);
-        assert_eq!(star_two(&input), 1);
-    }
 }
@@ -0,0 +1,315 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+
+use advent_of_rust_2022::bench::{self, Report};
+use advent_of_rust_2022::Part;
+
+const USAGE: &str = "Usage:
+    aoc run --day <1-25> --part <1|2> [--input <path|->]
+    aoc bench [--day <1-25>] [--input <path>] [--warmup <n>] [--iterations <n>]
+              [--output <path>] [--baseline <path>] [--threshold <percent>]
+
+Commands:
+    run     Runs a single part of a day's solution and prints the answer.
+    bench   Times parsing and both parts of every day, or a single day, with inputs.
+
+Options:
+    --day <n>               The day to run
+    --part <n>              The part to run, either 1 or 2
+    --input <path>          Where to read the puzzle input from, `-` reads from stdin.
+                            Defaults to `dayNN.txt` in the current directory.
+    --warmup <n>            Untimed runs before measuring, defaults to 3
+    --iterations <n>        Timed runs, defaults to 20
+    --output <path>         Write the benchmark results as JSON to this file
+    --baseline <path>       Compare against benchmark results previously written with
+                            `--output`, failing if any stage regressed
+    --threshold <percent>   How much slower the median may get before it's considered a
+                            regression, defaults to 10";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(answer) => println!("{answer}"),
+        Err(e) => {
+            eprintln!("{e}\n\nRun `aoc help` for usage.");
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    let (command, rest) = args
+        .split_first()
+        .ok_or_else(|| "No command given".to_owned())?;
+
+    match command.as_str() {
+        "run" => {
+            let options = Options::parse(rest)?;
+            let input = read_input(&options.input)?;
+
+            solve(options.day, options.part, &input)
+        }
+        "bench" => {
+            let options = BenchOptions::parse(rest)?;
+
+            bench(&options)
+        }
+        "help" | "--help" | "-h" => Ok(USAGE.to_owned()),
+        _ => Err(format!("Unknown command `{command}`")),
+    }
+}
+
+#[derive(Debug)]
+enum Input {
+    Stdin,
+    Path(String),
+}
+
+#[derive(Debug)]
+struct Options {
+    day: u8,
+    part: Part,
+    input: Input,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut day = None;
+        let mut part = None;
+        let mut input = None;
+        let mut args = args.iter();
+
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for `{flag}`"))?;
+
+            match flag.as_str() {
+                "--day" | "-d" => {
+                    let parsed: u8 = value
+                        .parse()
+                        .map_err(|e| format!("Invalid day `{value}`: {e}"))?;
+                    if !(1..=25).contains(&parsed) {
+                        return Err(format!("Day must be between 1 and 25, got {parsed}"));
+                    }
+
+                    day = Some(parsed);
+                }
+                "--part" | "-p" => {
+                    part = match value.as_str() {
+                        "1" => Some(Part::One),
+                        "2" => Some(Part::Two),
+                        _ => return Err(format!("Part must be either 1 or 2, got {value}")),
+                    };
+                }
+                "--input" | "-i" if value == "-" => input = Some(Input::Stdin),
+                "--input" | "-i" => input = Some(Input::Path(value.to_owned())),
+                _ => return Err(format!("Unknown option `{flag}`")),
+            }
+        }
+
+        let day = day.ok_or_else(|| "Missing required option `--day`".to_owned())?;
+        let part = part.ok_or_else(|| "Missing required option `--part`".to_owned())?;
+        let input = input.unwrap_or_else(|| Input::Path(format!("day{day:02}.txt")));
+
+        Ok(Self { day, part, input })
+    }
+}
+
+fn read_input(input: &Input) -> Result<String, String> {
+    let mut buffer = String::new();
+
+    match input {
+        Input::Stdin => io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|e| format!("Failed to read input from stdin: {e}"))?,
+        Input::Path(path) => File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut buffer))
+            .map_err(|e| format!("Failed to read input from `{path}`: {e}"))?,
+    };
+
+    Ok(buffer)
+}
+
+fn solve(day: u8, part: Part, input: &str) -> Result<String, String> {
+    let solution = advent_of_rust_2022::day(day)
+        .ok_or_else(|| format!("Day {day} has not been solved yet"))?;
+
+    solution
+        .solve(part, input)
+        .map_err(|e| format!("Failed to parse input for day {day}: {e}"))
+}
+
+#[derive(Debug)]
+struct BenchOptions {
+    day: Option<u8>,
+    input: Option<String>,
+    config: bench::Config,
+    output: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+}
+
+impl BenchOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            day: None,
+            input: None,
+            config: bench::Config::default(),
+            output: None,
+            baseline: None,
+            threshold: 0.1,
+        };
+        let mut args = args.iter();
+
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for `{flag}`"))?;
+            let invalid =
+                |e: &dyn std::fmt::Display| format!("Invalid value `{value}` for `{flag}`: {e}");
+
+            match flag.as_str() {
+                "--day" | "-d" => {
+                    let parsed: u8 = value.parse().map_err(|e| invalid(&e))?;
+                    if !(1..=25).contains(&parsed) {
+                        return Err(format!("Day must be between 1 and 25, got {parsed}"));
+                    }
+
+                    options.day = Some(parsed);
+                }
+                "--input" | "-i" => options.input = Some(value.to_owned()),
+                "--warmup" => options.config.warmup = value.parse().map_err(|e| invalid(&e))?,
+                "--iterations" => {
+                    options.config.iterations = value.parse().map_err(|e| invalid(&e))?
+                }
+                "--output" | "-o" => options.output = Some(value.to_owned()),
+                "--baseline" | "-b" => options.baseline = Some(value.to_owned()),
+                "--threshold" => {
+                    let percent: f64 = value.parse().map_err(|e| invalid(&e))?;
+                    options.threshold = percent / 100.0;
+                }
+                _ => return Err(format!("Unknown option `{flag}`")),
+            }
+        }
+
+        if options.input.is_some() && options.day.is_none() {
+            return Err("`--input` can only be used together with `--day`".to_owned());
+        }
+
+        Ok(options)
+    }
+}
+
+fn bench(options: &BenchOptions) -> Result<String, String> {
+    let days = match options.day {
+        Some(day) => day..=day,
+        None => 1..=25,
+    };
+    let mut report = Report::default();
+
+    for day in days {
+        let Some(solution) = advent_of_rust_2022::day(day) else {
+            continue;
+        };
+        let path = options
+            .input
+            .clone()
+            .unwrap_or_else(|| format!("day{day:02}.txt"));
+        let measurements = read_input(&Input::Path(path)).and_then(|input| {
+            bench::bench(day, solution, &input, options.config)
+                .map_err(|e| format!("Failed to parse input for day {day}: {e}"))
+        });
+
+        match measurements {
+            Ok(measurements) => {
+                for measurement in &measurements {
+                    eprintln!("{measurement}");
+                }
+
+                report.measurements.extend(measurements);
+            }
+            // Benchmarking a single day should fail loudly
+            Err(e) if options.day.is_some() => return Err(e),
+            Err(e) => eprintln!("Skipping day {day:02}: {e}"),
+        }
+    }
+
+    if let Some(output) = &options.output {
+        report.save(output)?;
+    }
+
+    let Some(baseline) = &options.baseline else {
+        return Ok(format!("Benchmarked {} stages", report.measurements.len()));
+    };
+    let regressions = report.regressions(&Report::load(baseline)?, options.threshold);
+
+    if regressions.is_empty() {
+        Ok(format!(
+            "Benchmarked {} stages, no regressions compared to `{baseline}`",
+            report.measurements.len()
+        ))
+    } else {
+        let lines: Vec<_> = regressions.iter().map(ToString::to_string).collect();
+
+        Err(format!(
+            "Found {} regressions compared to `{baseline}`:\n{}",
+            regressions.len(),
+            lines.join("\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, BenchOptions, Input, Options, Part};
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(ToOwned::to_owned).collect()
+    }
+
+    #[test]
+    fn test_options_parse() {
+        let options = Options::parse(&args("--day 7 --part 2 --input -")).unwrap();
+
+        assert_eq!(options.day, 7);
+        assert_eq!(options.part, Part::Two);
+        assert!(matches!(options.input, Input::Stdin));
+
+        let options = Options::parse(&args("--part 1 --day 3")).unwrap();
+        assert!(matches!(options.input, Input::Path(p) if p == "day03.txt"));
+    }
+
+    #[test]
+    fn test_options_parse_invalid() {
+        assert!(Options::parse(&args("--day 26 --part 1")).is_err());
+        assert!(Options::parse(&args("--day 1 --part 3")).is_err());
+        assert!(Options::parse(&args("--day 1")).is_err());
+        assert!(Options::parse(&args("--day 1 --part")).is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        assert_eq!(
+            run(&args("run --day 1 --part 1 --input day01.txt")),
+            Ok("66616".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_bench_options_parse() {
+        let options =
+            BenchOptions::parse(&args("--day 4 --iterations 5 --warmup 0 --threshold 25")).unwrap();
+
+        assert_eq!(options.day, Some(4));
+        assert_eq!(options.config.iterations, 5);
+        assert_eq!(options.config.warmup, 0);
+        assert!((options.threshold - 0.25).abs() < f64::EPSILON);
+
+        assert!(BenchOptions::parse(&args("--input day04.txt")).is_err());
+        assert!(BenchOptions::parse(&args("--iterations many")).is_err());
+    }
+}
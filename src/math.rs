@@ -1,6 +1,8 @@
 use std::fmt;
 use std::hash::Hash;
-use std::ops::{Add, Neg, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use num_traits::{One, Zero};
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
 pub struct Vector2<T> {
@@ -14,6 +16,12 @@ impl<T> Vector2<T> {
     }
 }
 
+impl<T> From<(T, T)> for Vector2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
 impl<T: Default> Default for Vector2<T> {
     fn default() -> Self {
         Self {
@@ -62,28 +70,276 @@ impl<T: Neg<Output = T> + Hash + PartialEq> Neg for Vector2<T> {
     }
 }
 
+impl<T: AddAssign> AddAssign for Vector2<T> {
+    fn add_assign(&mut self, rhs: Vector2<T>) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Vector2<T> {
+    fn sub_assign(&mut self, rhs: Vector2<T>) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Vector2::<T> {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl<T: Div<Output = T> + Copy> Div<T> for Vector2<T> {
+    type Output = Vector2<T>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Vector2::<T> {
+            x: self.x / rhs,
+            y: self.y / rhs,
+        }
+    }
+}
+
+impl<T: Neg<Output = T>> Vector2<T> {
+    /// Rotate by 90° clockwise, with y pointing down like on a map, so up turns right.
+    pub fn rotate_right(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Rotate by 90° counterclockwise, with y pointing down like on a map, so up turns left.
+    pub fn rotate_left(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+}
+
+impl<T: Signum> Vector2<T> {
+    /// The sign of each component, the single step towards this vector along both axes.
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+}
+
+impl<T> Vector2<T>
+where
+    T: Copy + Zero + One + Neg<Output = T>,
+{
+    /// The orthogonal neighbours, clockwise from up with y pointing down.
+    pub fn neighbours4(self) -> impl Iterator<Item = Self> {
+        let (zero, one) = (T::zero(), T::one());
+
+        [(zero, -one), (one, zero), (zero, one), (-one, zero)]
+            .into_iter()
+            .map(move |offset| self + offset.into())
+    }
+
+    /// The orthogonal and diagonal neighbours, clockwise from up with y pointing down.
+    pub fn neighbours8(self) -> impl Iterator<Item = Self> {
+        let (zero, one) = (T::zero(), T::one());
+
+        [
+            (zero, -one),
+            (one, -one),
+            (one, zero),
+            (one, one),
+            (zero, one),
+            (-one, one),
+            (-one, zero),
+            (-one, -one),
+        ]
+        .into_iter()
+        .map(move |offset| self + offset.into())
+    }
+}
+
+#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Vector3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Vector3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Default> Default for Vector3<T> {
+    fn default() -> Self {
+        Self {
+            x: T::default(),
+            y: T::default(),
+            z: T::default(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Vector3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<Vector3 x={:?} y={:?} z={:?} >", self.x, self.y, self.z)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vector3<T> {
+    type Output = Vector3<T>;
+
+    fn add(self, rhs: Vector3<T>) -> Self::Output {
+        Vector3::<T> {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vector3<T> {
+    type Output = Vector3<T>;
+
+    fn sub(self, rhs: Vector3<T>) -> Self::Output {
+        Vector3::<T> {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Vector3<T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Vector3::<T> {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vector3<T> {
+    type Output = Vector3<T>;
+
+    fn neg(self) -> Self::Output {
+        Vector3::<T> {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
 pub trait Abs {
     type Output;
 
     fn abs(self) -> Self::Output;
 }
 
-macro_rules! define_abs {
-    ($T:ident) => {
-        impl Abs for $T {
-            type Output = $T;
+pub trait Signum {
+    fn signum(self) -> Self;
+}
+
+macro_rules! define_signed {
+    ($($T:ident),*) => {
+        $(
+            impl Abs for $T {
+                type Output = $T;
 
-            fn abs(self) -> Self::Output {
-                self.abs()
+                fn abs(self) -> Self::Output {
+                    self.abs()
+                }
             }
-        }
+
+            impl Signum for $T {
+                fn signum(self) -> Self {
+                    self.signum()
+                }
+            }
+        )*
     };
 }
 
-define_abs!(isize);
+define_signed!(i8, i16, i32, i64, i128, isize);
 
 impl<T: Abs<Output = T> + Sub<Output = T> + Add<Output = T>> Vector2<T> {
     pub fn manhattan_distance(self, other: Self) -> T {
         (self.x - other.x).abs() + (self.y - other.y).abs()
     }
 }
+
+impl<T: Abs<Output = T> + Sub<Output = T> + Ord> Vector2<T> {
+    /// The distance when diagonal steps count as one, the number of king moves on a chessboard.
+    pub fn chebyshev_distance(self, other: Self) -> T {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+}
+
+impl<T: Abs<Output = T> + Sub<Output = T> + Add<Output = T>> Vector3<T> {
+    pub fn manhattan_distance(self, other: Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vector2;
+
+    #[test]
+    fn test_scalar_ops() {
+        let mut v = Vector2::new(3, -4);
+        assert_eq!(v * 2, Vector2::new(6, -8));
+        assert_eq!(v / 2, Vector2::new(1, -2));
+
+        v += Vector2::new(1, 1);
+        assert_eq!(v, Vector2::new(4, -3));
+        v -= Vector2::from((4, 4));
+        assert_eq!(v, Vector2::new(0, -7));
+    }
+
+    #[test]
+    fn test_signum() {
+        assert_eq!(Vector2::new(-5i8, 0).signum(), Vector2::new(-1, 0));
+        assert_eq!(Vector2::new(7i128, -2).signum(), Vector2::new(1, -1));
+    }
+
+    #[test]
+    fn test_rotations() {
+        let up = Vector2::new(0, -1);
+        let right = up.rotate_right();
+        assert_eq!(right, Vector2::new(1, 0));
+        assert_eq!(right.rotate_right(), Vector2::new(0, 1));
+        assert_eq!(up.rotate_left(), Vector2::new(-1, 0));
+
+        let v = Vector2::new(3, 5);
+        assert_eq!(v.rotate_right().rotate_left(), v);
+        assert_eq!(v.rotate_left().rotate_left(), -v);
+    }
+
+    #[test]
+    fn test_distances() {
+        let (a, b) = (Vector2::new(1i32, 2), Vector2::new(-3, 4));
+        assert_eq!(a.manhattan_distance(b), 6);
+        assert_eq!(a.chebyshev_distance(b), 4);
+        assert_eq!(b.chebyshev_distance(a), 4);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let p = Vector2::new(2i64, 3);
+        let orthogonal: Vec<_> = p.neighbours4().collect();
+        assert_eq!(
+            orthogonal,
+            [(2, 2), (3, 3), (2, 4), (1, 3)].map(Vector2::from)
+        );
+
+        let all: Vec<_> = p.neighbours8().collect();
+        assert_eq!(all.len(), 8);
+        assert!(all.iter().all(|&n| p.chebyshev_distance(n) == 1));
+        assert!(orthogonal.iter().all(|n| all.contains(n)));
+    }
+}
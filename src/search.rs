@@ -0,0 +1,345 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use num_traits::Zero;
+
+/// What a search found, the shortest distance to every node it settled and how it got there.
+///
+/// A node is settled once its shortest distance is known. When the search stops early at a goal
+/// nodes it has seen, but not settled yet, are left out.
+#[derive(Debug, Clone)]
+pub struct SearchResult<N, C> {
+    distances: HashMap<N, C>,
+    /// The node each node was reached from on its shortest path, starts have none.
+    came_from: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N, C> SearchResult<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy,
+{
+    fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            came_from: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    /// The distance from the closest start to `node`, [`None`] if it wasn't settled.
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    /// Every settled node with its distance from the closest start.
+    pub fn distances(&self) -> &HashMap<N, C> {
+        &self.distances
+    }
+
+    /// The goal the search stopped at, [`None`] if it never reached one.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// The distance to the goal the search stopped at.
+    pub fn goal_distance(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.distance(goal))
+    }
+
+    /// The nodes from a start to `node`, both included, [`None`] if `node` wasn't settled.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.distances.contains_key(node) {
+            return None;
+        }
+
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self
+            .came_from
+            .get(path.last().expect("Path is never empty"))
+        {
+            path.push(previous.clone());
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Breadth first search from every node in `starts` at once, where every step costs one.
+///
+/// Stops at the first node `is_goal` accepts, pass `|_| false` to reach every node. Every step
+/// costing the same, nodes are settled as soon as they're seen.
+pub fn bfs<N, S, FN, I, FG>(
+    starts: S,
+    mut neighbours: FN,
+    mut is_goal: FG,
+) -> SearchResult<N, usize>
+where
+    N: Eq + Hash + Clone,
+    S: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut result = SearchResult::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if result.distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(current) = queue.pop_front() {
+        if is_goal(&current) {
+            result.goal = Some(current);
+            break;
+        }
+        let distance = result.distances[&current];
+
+        for next in neighbours(&current) {
+            if result.distances.contains_key(&next) {
+                continue;
+            }
+
+            result.distances.insert(next.clone(), distance + 1);
+            result.came_from.insert(next.clone(), current.clone());
+            queue.push_back(next);
+        }
+    }
+
+    result
+}
+
+/// Dijkstra's algorithm from every node in `starts` at once, `neighbours` gives the cost of
+/// moving to each neighbour.
+///
+/// Stops at the first node `is_goal` accepts, pass `|_| false` to reach every node.
+pub fn dijkstra<N, C, S, FN, I, FG>(starts: S, neighbours: FN, is_goal: FG) -> SearchResult<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Ord + Copy + Zero + Add<Output = C>,
+    S: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(starts, neighbours, |_| C::zero(), is_goal)
+}
+
+/// A* search from every node in `starts` at once, `neighbours` gives the cost of moving to each
+/// neighbour.
+///
+/// `heuristic` estimates the remaining cost to a goal. It must never overestimate and be
+/// consistent, for the distance to the goal to be the shortest. Stops at the first node
+/// `is_goal` accepts.
+pub fn astar<N, C, S, FN, I, FH, FG>(
+    starts: S,
+    mut neighbours: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> SearchResult<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Ord + Copy + Zero + Add<Output = C>,
+    S: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    let mut result = SearchResult::new();
+    // The best known distance and previous node of every node seen, settled or not
+    let mut best = HashMap::new();
+    let mut previous = HashMap::new();
+    let mut open = BinaryHeap::new();
+    for start in starts {
+        if best.insert(start.clone(), C::zero()).is_none() {
+            open.push(Entry {
+                priority: heuristic(&start),
+                cost: C::zero(),
+                node: start,
+            });
+        }
+    }
+
+    while let Some(Entry { cost, node, .. }) = open.pop() {
+        // Settled already, or a cheaper way to this node was found after this entry was queued
+        if result.distances.contains_key(&node) || best[&node] < cost {
+            continue;
+        }
+        result.distances.insert(node.clone(), cost);
+        if let Some(from) = previous.remove(&node) {
+            result.came_from.insert(node.clone(), from);
+        }
+        if is_goal(&node) {
+            result.goal = Some(node);
+            break;
+        }
+
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if best.get(&next).is_some_and(|&best| best <= next_cost) {
+                continue;
+            }
+
+            best.insert(next.clone(), next_cost);
+            previous.insert(next.clone(), node.clone());
+            open.push(Entry {
+                priority: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+
+    result
+}
+
+/// A queued node, ordered so the [`BinaryHeap`] pops the lowest priority first.
+struct Entry<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Entry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for Entry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Entry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Entry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, dijkstra};
+    use crate::{grid::Grid, math::Vector2};
+
+    const MAZE: &str = r#"
+.....
+.###.
+...#.
+.#.#.
+.#...
+"#;
+
+    fn maze() -> Grid<bool> {
+        Grid::parse(MAZE, |c| Some(c == '#')).unwrap()
+    }
+
+    fn open_neighbours(maze: &Grid<bool>, p: Vector2<isize>) -> Vec<Vector2<isize>> {
+        maze.neighbours4(p)
+            .filter(|&(_, &wall)| !wall)
+            .map(|(n, _)| n)
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze();
+        let (start, goal) = (Vector2::new(0, 0), Vector2::new(4, 4));
+
+        let result = bfs([start], |&p| open_neighbours(&maze, p), |&p| p == goal);
+        assert_eq!(result.goal(), Some(&goal));
+        assert_eq!(result.goal_distance(), Some(8));
+
+        let path = result.path_to(&goal).unwrap();
+        assert_eq!(path.len(), 9);
+        assert_eq!((path[0], path[8]), (start, goal));
+        assert!(path.windows(2).all(|w| w[0].manhattan_distance(w[1]) == 1));
+
+        // Walls are never reached
+        let result = bfs([start], |&p| open_neighbours(&maze, p), |_| false);
+        assert_eq!(result.goal(), None);
+        assert_eq!(result.distances().len(), 18);
+        assert_eq!(result.distance(&Vector2::new(1, 1)), None);
+        assert_eq!(result.path_to(&Vector2::new(1, 1)), None);
+    }
+
+    #[test]
+    fn test_multi_source() {
+        let maze = maze();
+        let starts = [Vector2::new(0, 0), Vector2::new(4, 4)];
+
+        let result = bfs(starts, |&p| open_neighbours(&maze, p), |_| false);
+        assert_eq!(result.distance(&Vector2::new(4, 0)), Some(4));
+        assert_eq!(result.distance(&Vector2::new(2, 3)), Some(3));
+        assert_eq!(result.path_to(&starts[1]), Some(vec![starts[1]]));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Going the long way round is cheaper than the direct edge
+        let edges = |&n: &u8| -> Vec<(u8, u32)> {
+            match n {
+                0 => vec![(1, 1), (3, 10)],
+                1 => vec![(2, 2)],
+                2 => vec![(3, 3)],
+                _ => vec![],
+            }
+        };
+
+        let result = dijkstra([0], edges, |&n| n == 3);
+        assert_eq!(result.goal_distance(), Some(6));
+        assert_eq!(result.path_to(&3), Some(vec![0, 1, 2, 3]));
+
+        let result = dijkstra([0], edges, |&n| n == 4);
+        assert_eq!(result.goal(), None);
+    }
+
+    #[test]
+    fn test_early_stop() {
+        // 2 is first seen at a distance of 10, but only settled at 2 after the goal
+        let edges = |&n: &u8| -> Vec<(u8, u32)> {
+            match n {
+                0 => vec![(2, 10), (1, 1)],
+                1 => vec![(2, 1)],
+                _ => vec![],
+            }
+        };
+
+        let result = dijkstra([0], edges, |&n| n == 1);
+        assert_eq!(result.goal_distance(), Some(1));
+        assert_eq!(result.distance(&2), None);
+        assert_eq!(result.path_to(&2), None);
+        assert_eq!(result.distances().len(), 2);
+
+        let result = dijkstra([0], edges, |_| false);
+        assert_eq!(result.distance(&2), Some(2));
+        assert_eq!(result.path_to(&2), Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_astar_matches_bfs() {
+        let maze = maze();
+        let start = Vector2::new(0, 0);
+        let reachable = bfs([start], |&p| open_neighbours(&maze, p), |_| false);
+
+        for (goal, &distance) in reachable.distances() {
+            let result = astar(
+                [start],
+                |&p| open_neighbours(&maze, p).into_iter().map(|n| (n, 1)),
+                |p| p.manhattan_distance(*goal),
+                |p| p == goal,
+            );
+
+            assert_eq!(result.goal_distance(), Some(distance as isize));
+        }
+    }
+}